@@ -102,9 +102,11 @@ fn main() {
     // Now the set holds a `Foo` containing the string `Wilder!`.
     assert_eq!(set.as_ref() as &Foo, &Foo("Wilder!".to_string()));
 
-    // Finally, the types represented by the set can be iterated.
+    // Finally, the types represented by the set can be iterated. The short
+    // form of each type's parsed name keeps its generic arguments and
+    // references, but drops its module path.
     for t in set.types() {
-        match t.as_name() {
+        match t.parsed_name().short().to_string().as_str() {
             "u8" => {
                 println!("Set holds a u8: {}", set.as_ref() as &u8);
             }
@@ -114,7 +116,7 @@ fn main() {
             "u32" => {
                 println!("Set holds a u32: {}", set.as_ref() as &u32);
             }
-            "str" => {
+            "&str" => {
                 println!("Set holds a &str: '{}'", set.as_ref() as &&str);
             }
             "String" => {
@@ -0,0 +1,286 @@
+//! Derive macros for the [`singletonset`] crate.
+//!
+//! These macros are re-exported by `singletonset` when its `derive`
+//! feature is enabled, so this crate should not need to be used directly.
+//!
+//! [`singletonset`]: https://docs.rs/singletonset
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Error, Fields,
+    GenericArgument, Member, PathArguments, Type,
+};
+
+/// Implements `FromSingletonSet` for a struct, so it can be built out of
+/// the values held by a `SingletonSet`.
+///
+/// Each field is filled from the slot of the field's type. By default, the
+/// value is taken out of the set, and the set must hold a value for every
+/// field. The following field attributes change this:
+///
+/// - `#[singleton(clone)]` clones the value and leaves it in the set.
+/// - `#[singleton(optional)]` is used on `Option<T>` fields, which are set
+///   to [`None`] if the set has no `T`.
+/// - `#[singleton(default)]` uses the type's [`Default`] value if the set
+///   has no value of that type.
+#[proc_macro_derive(FromSingletonSet, attributes(singleton))]
+pub fn derive_from_singleton_set(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_from(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `IntoSingletonSet` for a struct, so each of its fields can
+/// be inserted into the slot of the field's type.
+///
+/// Fields marked `#[singleton(optional)]` must be `Option<T>`, and insert
+/// their `T` only if they hold a value.
+#[proc_macro_derive(IntoSingletonSet, attributes(singleton))]
+pub fn derive_into_singleton_set(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand_into(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    clone: bool,
+    default: bool,
+    optional: bool,
+}
+
+struct Field<'a> {
+    member: Member,
+    name: String,
+    /// The type of the slot the field is stored in, which is the inner type
+    /// for `optional` fields.
+    slot: &'a Type,
+    options: FieldOptions,
+}
+
+fn parse_fields(input: &DeriveInput) -> syn::Result<Vec<Field<'_>>> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "singletonset derives only support structs",
+            ))
+        }
+    };
+
+    let fields = match fields {
+        Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    fields
+        .into_iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let (member, name) = match &field.ident {
+                Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+                None => (Member::Unnamed(i.into()), i.to_string()),
+            };
+
+            let mut options = FieldOptions::default();
+
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|a| a.path().is_ident("singleton"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("clone") {
+                        options.clone = true;
+                    } else if meta.path.is_ident("default") {
+                        options.default = true;
+                    } else if meta.path.is_ident("optional") {
+                        options.optional = true;
+                    } else {
+                        return Err(meta.error("expected `clone`, `default` or `optional`"));
+                    }
+                    Ok(())
+                })?;
+            }
+
+            if options.default && options.optional {
+                return Err(Error::new(
+                    field.span(),
+                    "a field cannot be both `default` and `optional`",
+                ));
+            }
+
+            let slot = if options.optional {
+                option_inner(&field.ty).ok_or_else(|| {
+                    Error::new(field.ty.span(), "`optional` fields must be `Option<T>`")
+                })?
+            } else {
+                &field.ty
+            };
+
+            Ok(Field {
+                member,
+                name,
+                slot,
+                options,
+            })
+        })
+        .collect()
+}
+
+/// Returns `T` if `ty` is written as `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn expand_from(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = parse_fields(input)?;
+    let ident = &input.ident;
+
+    let mut generics = input.generics.clone();
+    let predicates = &mut generics.make_where_clause().predicates;
+    for field in &fields {
+        let slot = field.slot;
+        predicates.push(parse_quote!(#slot: 'static));
+        if field.options.clone {
+            predicates.push(parse_quote!(#slot: ::core::clone::Clone));
+        }
+        if field.options.default {
+            predicates.push(parse_quote!(#slot: ::core::default::Default));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let checks = fields
+        .iter()
+        .filter(|f| !f.options.default && !f.options.optional)
+        .map(|f| {
+            let (slot, name) = (f.slot, &f.name);
+            quote! {
+                ::singletonset::__private::check::<#slot>(set, #name, &mut missing);
+            }
+        });
+
+    // Fields that take their values fail up front if a value can't be
+    // taken, or is taken by an earlier field, so the set is never left half
+    // emptied.
+    let owned_checks = fields.iter().filter(|f| !f.options.clone).map(|f| {
+        let (slot, name) = (f.slot, &f.name);
+        quote! {
+            ::singletonset::__private::check_owned::<#slot>(set, #name, &mut not_owned);
+            ::singletonset::__private::check_unique::<#slot>(#name, &mut taken, &mut duplicated);
+        }
+    });
+
+    let value = |f: &Field<'_>| {
+        let (slot, name) = (f.slot, &f.name);
+        let o = &f.options;
+        match (o.clone, o.optional, o.default) {
+            (true, true, _) => quote!(set.try_get::<#slot>().cloned()),
+            (true, _, true) => quote!(set.try_get::<#slot>().cloned().unwrap_or_default()),
+            (true, ..) => quote!(::core::clone::Clone::clone(set.get::<#slot>())),
+            (false, true, _) => quote!(set.remove::<#slot>()),
+            (false, _, true) => quote!(set.remove::<#slot>().unwrap_or_default()),
+            (false, ..) => quote!(::singletonset::__private::take::<#slot>(set, #name)),
+        }
+    };
+
+    // Cloned fields are read before any field is taken, so a type can be
+    // both cloned into one field and moved into another.
+    let ordered = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.options.clone)
+        .chain(fields.iter().enumerate().filter(|(_, f)| !f.options.clone));
+
+    let bindings = ordered.map(|(i, f)| {
+        let binding = format_ident!("__field{}", i);
+        let value = value(f);
+        quote!(let #binding = #value;)
+    });
+
+    let members = fields.iter().enumerate().map(|(i, f)| {
+        let member = &f.member;
+        let binding = format_ident!("__field{}", i);
+        quote!(#member: #binding)
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::singletonset::FromSingletonSet for #ident #ty_generics #where_clause {
+            fn from_singleton_set(
+                set: &mut ::singletonset::SingletonSet,
+            ) -> ::core::result::Result<Self, ::singletonset::MissingFields> {
+                let mut missing = ::std::vec::Vec::new();
+                let mut not_owned = ::std::vec::Vec::new();
+                let mut taken = ::std::vec::Vec::new();
+                let mut duplicated = ::std::vec::Vec::new();
+                #(#checks)*
+                #(#owned_checks)*
+                ::singletonset::__private::missing(missing, not_owned, duplicated)?;
+
+                #(#bindings)*
+
+                ::core::result::Result::Ok(Self { #(#members,)* })
+            }
+        }
+    })
+}
+
+fn expand_into(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = parse_fields(input)?;
+    let ident = &input.ident;
+
+    let mut generics = input.generics.clone();
+    let predicates = &mut generics.make_where_clause().predicates;
+    for field in &fields {
+        let slot = field.slot;
+        predicates.push(parse_quote!(#slot: 'static));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let inserts = fields.iter().map(|f| {
+        let member = &f.member;
+        if f.options.optional {
+            quote! {
+                if let ::core::option::Option::Some(value) = self.#member {
+                    set.insert(value);
+                }
+            }
+        } else {
+            quote!(set.insert(self.#member);)
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::singletonset::IntoSingletonSet for #ident #ty_generics #where_clause {
+            fn insert_into_set(self, set: &mut ::singletonset::SingletonSet) {
+                #(#inserts)*
+            }
+        }
+    })
+}
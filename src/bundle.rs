@@ -0,0 +1,295 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use crate::{SingletonSet, Type, TypeSet};
+
+/// A group of types that can be inserted into or taken out of a
+/// [`SingletonSet`] together.
+///
+/// `Bundle` is implemented for tuples of up to twelve `'static` types, so
+/// a tuple of values can be inserted with a single call to
+/// [`.insert_bundle()`], and a tuple type can be named to check for,
+/// borrow, or remove several slots at once.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::SingletonSet;
+///
+/// let mut set = SingletonSet::new();
+/// set.insert_bundle((1u8, 2u16, "three".to_string()));
+///
+/// assert!(set.contains_all::<(u8, u16)>());
+/// assert_eq!(set.try_get_all::<(u8, String)>(), Some((&1, &"three".to_string())));
+///
+/// let (a, b) = set.take_bundle::<(u8, u16)>().unwrap();
+/// assert_eq!((a, b), (1, 2));
+/// assert_eq!(set.len(), 1);
+/// ```
+///
+/// [`.insert_bundle()`]: SingletonSet::insert_bundle()
+pub trait Bundle: Sized + 'static {
+    /// Returns the [`Type`] of each member of the bundle.
+    ///
+    /// [`Type`]: crate::Type
+    fn types() -> TypeSet;
+
+    /// Inserts each member of the bundle into its slot in `set`, replacing
+    /// any existing values.
+    fn insert_into(self, set: &mut SingletonSet);
+
+    /// Removes each member of the bundle from `set`.
+    ///
+    /// Either every member is removed, or, if any of them is missing, held
+    /// in a slot whose value can't be returned, or held in a sealed slot,
+    /// the set is left untouched and the error lists every such [`Type`].
+    ///
+    /// [`Type`]: crate::Type
+    fn take_from(set: &mut SingletonSet) -> Result<Self, MissingTypes>;
+}
+
+/// Borrows every member of a [`Bundle`] from a [`SingletonSet`] at once.
+///
+/// This is implemented for the same tuples as [`Bundle`], and is what
+/// allows [`.try_get_all()`] to return a tuple of references.
+///
+/// [`.try_get_all()`]: SingletonSet::try_get_all()
+pub trait BundleRef<'a>: Bundle {
+    /// A tuple holding a reference to each member of the bundle.
+    type Refs;
+
+    /// Returns a reference to each member of the bundle, or [`None`] if any
+    /// of them is missing from `set`.
+    fn get_from(set: &'a SingletonSet) -> Option<Self::Refs>;
+}
+
+/// The error returned when one or more types of a [`Bundle`] are missing
+/// from a [`SingletonSet`], or can't be taken out of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingTypes(
+    pub(crate) TypeSet,
+    pub(crate) Vec<Type>,
+    pub(crate) Vec<Type>,
+);
+
+impl MissingTypes {
+    /// Returns every [`Type`] that was missing, in the order they appear in
+    /// the bundle.
+    pub fn types(&self) -> &TypeSet {
+        &self.0
+    }
+
+    /// Returns every [`Type`] whose value the set holds but doesn't own
+    /// outright, such as a pinned value or a shared value that other `Arc`s
+    /// still point to, in the order they appear in the bundle.
+    pub fn not_owned(&self) -> &[Type] {
+        &self.1
+    }
+
+    /// Returns every [`Type`] whose slot is sealed, in the order they
+    /// appear in the bundle.
+    pub fn sealed(&self) -> &[Type] {
+        &self.2
+    }
+}
+
+impl Display for MissingTypes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lists = [
+            ("missing types", self.0.iter().collect::<Vec<_>>()),
+            ("types not owned", self.1.iter().collect()),
+            ("sealed types", self.2.iter().collect()),
+        ];
+        let lists = lists.iter().filter(|(_, types)| !types.is_empty());
+
+        for (i, (label, types)) in lists.enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}: ", label)?;
+
+            for (i, t) in types.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", t)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for MissingTypes {}
+
+macro_rules! impl_bundle {
+    ($($name:ident),+) => {
+        impl<$($name),+> Bundle for ($($name,)+)
+        where
+            $($name: 'static,)+
+        {
+            fn types() -> TypeSet {
+                let mut types = TypeSet::new();
+                $(types.insert_type::<$name>();)+
+                types
+            }
+
+            #[allow(non_snake_case)]
+            fn insert_into(self, set: &mut SingletonSet) {
+                let ($($name,)+) = self;
+                $(set.insert($name);)+
+            }
+
+            fn take_from(set: &mut SingletonSet) -> Result<Self, MissingTypes> {
+                let types = Self::types();
+
+                assert_eq!(
+                    types.len(),
+                    [$(stringify!($name)),+].len(),
+                    "a bundle must not name the same type more than once",
+                );
+
+                let missing = set.missing_types(&types);
+                let mut not_owned = Vec::new();
+                $(if set.is_not_owned::<$name>() {
+                    not_owned.push(Type::of::<$name>());
+                })+
+                let sealed: Vec<_> = types
+                    .iter()
+                    .filter(|t| set.1.check_unsealed(t).is_err())
+                    .copied()
+                    .collect();
+                if !missing.is_empty() || !not_owned.is_empty() || !sealed.is_empty() {
+                    return Err(MissingTypes(missing, not_owned, sealed));
+                }
+
+                Ok(($(
+                    set.remove::<$name>()
+                        // Safety: Every type was just checked to be present,
+                        // owned by the set and unsealed, and each one appears
+                        // only once.
+                        .unwrap(),
+                )+))
+            }
+        }
+
+        impl<'a, $($name),+> BundleRef<'a> for ($($name,)+)
+        where
+            $($name: 'static,)+
+        {
+            type Refs = ($(&'a $name,)+);
+
+            fn get_from(set: &'a SingletonSet) -> Option<Self::Refs> {
+                Some(($(set.try_get::<$name>()?,)+))
+            }
+        }
+    };
+}
+
+impl_bundle!(A);
+impl_bundle!(A, B);
+impl_bundle!(A, B, C);
+impl_bundle!(A, B, C, D);
+impl_bundle!(A, B, C, D, E);
+impl_bundle!(A, B, C, D, E, F);
+impl_bundle!(A, B, C, D, E, F, G);
+impl_bundle!(A, B, C, D, E, F, G, H);
+impl_bundle!(A, B, C, D, E, F, G, H, I);
+impl_bundle!(A, B, C, D, E, F, G, H, I, J);
+impl_bundle!(A, B, C, D, E, F, G, H, I, J, K);
+impl_bundle!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_bundle_is_all_or_nothing() {
+        let mut set = SingletonSet::new();
+        set.insert_bundle((1u8, 2u16));
+
+        let err = set.take_bundle::<(u8, u32, u16, u64)>().unwrap_err();
+
+        assert_eq!(
+            err.types().iter().copied().collect::<Vec<_>>(),
+            [Type::of::<u32>(), Type::of::<u64>()]
+        );
+        assert_eq!(set.len(), 2);
+        assert_eq!(err.to_string(), "missing types: u32, u64");
+
+        assert_eq!(set.take_bundle::<(u16, u8)>(), Ok((2, 1)));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn take_bundle_leaves_shared_values_alone() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert_shared(2u16);
+        let arc = set.get_arc::<u16>().unwrap();
+
+        let err = set.take_bundle::<(u8, u16)>().unwrap_err();
+        assert!(err.types().is_empty());
+        assert_eq!(err.not_owned(), [Type::of::<u16>()]);
+        assert_eq!(err.to_string(), "types not owned: u16");
+        assert_eq!(set.len(), 2);
+
+        drop(arc);
+        assert_eq!(set.take_bundle::<(u8, u16)>(), Ok((1, 2)));
+    }
+
+    #[test]
+    fn take_bundle_leaves_pinned_values_alone() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert_pinned(2u16);
+        set.insert_shared(3u32);
+        let _arc = set.get_arc::<u32>().unwrap();
+
+        let err = set.take_bundle::<(u8, u16, u32, u64)>().unwrap_err();
+        assert!(err.types().contains::<u64>());
+        assert_eq!(err.not_owned(), [Type::of::<u16>(), Type::of::<u32>()]);
+        assert_eq!(
+            err.to_string(),
+            "missing types: u64; types not owned: u16, u32"
+        );
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.get::<u16>(), &2);
+    }
+
+    #[test]
+    fn take_bundle_leaves_sealed_and_unsized_values_alone() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert_unsized::<u16>(Box::new(2));
+        let _token = set.seal::<u8>().unwrap();
+
+        let err = set.take_bundle::<(u8, u16)>().unwrap_err();
+        assert_eq!(err.not_owned(), [Type::of::<u16>()]);
+        assert_eq!(err.sealed(), [Type::of::<u8>()]);
+        assert_eq!(err.to_string(), "types not owned: u16; sealed types: u8");
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn try_get_all_requires_every_type() {
+        let mut set = SingletonSet::new();
+        set.insert_bundle((1u8, "foo"));
+
+        assert!(set.contains_all::<(u8, &str)>());
+        assert!(!set.contains_all::<(u8, u16)>());
+        assert_eq!(set.try_get_all::<(&str, u8)>(), Some((&"foo", &1)));
+        assert_eq!(set.try_get_all::<(u8, u16)>(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn take_bundle_rejects_repeated_types() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+
+        let _ = set.take_bundle::<(u8, u8)>();
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    any::Any,
+    fmt::{Debug, Formatter},
+};
+
+use once_cell::unsync::OnceCell;
+
+use crate::{BundleRef, SingletonSet, Type, TypeSet};
+
+/// Computes a value from the contents of a set, or returns [`None`] if an
+/// input is missing.
+type ComputeFn = dyn Fn(&SingletonSet) -> Option<Box<dyn Any>>;
+
+/// A value computed from the values of other slots, registered with
+/// [`SingletonSet::register_computed()`].
+///
+/// The value is computed the first time it is read, and kept until one of
+/// its inputs changes.
+///
+/// [`SingletonSet::register_computed()`]: crate::SingletonSet::register_computed()
+pub(crate) struct Computed {
+    inputs: TypeSet,
+    compute: Box<ComputeFn>,
+    /// The memoized result. An inner [`None`] means that an input was
+    /// missing when the value was last computed.
+    value: OnceCell<Option<Box<dyn Any>>>,
+}
+
+impl Computed {
+    pub(crate) fn new<Out, B>(
+        f: impl for<'a> Fn(<B as BundleRef<'a>>::Refs) -> Out + 'static,
+    ) -> Self
+    where
+        Out: 'static,
+        B: for<'a> BundleRef<'a>,
+    {
+        Computed {
+            inputs: B::types(),
+            compute: Box::new(move |set| {
+                B::get_from(set).map(|refs| Box::new(f(refs)) as Box<dyn Any>)
+            }),
+            value: OnceCell::new(),
+        }
+    }
+
+    /// Returns the memoized value, computing it from `set` first if needed.
+    pub(crate) fn get<'a>(&'a self, set: &'a SingletonSet) -> Option<&'a dyn Any> {
+        self.value.get_or_init(|| (self.compute)(set)).as_deref()
+    }
+
+    pub(crate) fn depends_on(&self, t: &Type) -> bool {
+        self.inputs.contains_type(t)
+    }
+
+    /// Forgets the memoized value, and returns true if there was one.
+    pub(crate) fn invalidate(&mut self) -> bool {
+        self.value.take().is_some()
+    }
+}
+
+impl Debug for Computed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Computed")
+            .field("inputs", &self.inputs)
+            .field("memoized", &self.value.get().is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::SingletonSet;
+
+    struct Config {
+        timeout: u32,
+    }
+
+    struct Overrides {
+        timeout: Option<u32>,
+    }
+
+    struct Timeout(u32);
+
+    struct Doubled(u32);
+
+    #[test]
+    fn computed_values_are_memoized_until_an_input_changes() {
+        let runs = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&runs);
+
+        let mut set = SingletonSet::new();
+        set.insert(Config { timeout: 30 });
+        set.register_computed::<Timeout, (Config, Overrides)>(move |(config, overrides)| {
+            counter.set(counter.get() + 1);
+            Timeout(overrides.timeout.unwrap_or(config.timeout))
+        });
+
+        assert!(set.try_get::<Timeout>().is_none());
+        set.insert(Overrides { timeout: None });
+        assert_eq!(set.get::<Timeout>().0, 30);
+        assert_eq!(set.get::<Timeout>().0, 30);
+        assert_eq!(runs.get(), 1);
+
+        set.insert(1u8);
+        let _ = set.try_get_mut::<u8>();
+        assert_eq!(set.get::<Timeout>().0, 30);
+        assert_eq!(runs.get(), 1);
+
+        set.try_get_mut::<Overrides>().unwrap().timeout = Some(5);
+        assert_eq!(set.get::<Timeout>().0, 5);
+        assert_eq!(runs.get(), 2);
+
+        set.remove::<Config>();
+        assert!(set.try_get::<Timeout>().is_none());
+        assert!(!set.contains::<Timeout>());
+    }
+
+    #[test]
+    fn computed_values_can_depend_on_each_other() {
+        let mut set = SingletonSet::new();
+        set.insert(Config { timeout: 10 });
+        set.insert(Overrides { timeout: None });
+        set.register_computed::<Timeout, (Config, Overrides)>(|(config, overrides)| {
+            Timeout(overrides.timeout.unwrap_or(config.timeout))
+        });
+        set.register_computed::<Doubled, (Timeout,)>(|(timeout,)| Doubled(timeout.0 * 2));
+
+        assert_eq!(set.get::<Doubled>().0, 20);
+
+        set.insert(Config { timeout: 4 });
+        assert_eq!(set.get::<Doubled>().0, 8);
+
+        set.insert(Timeout(1));
+        assert_eq!(set.get::<Doubled>().0, 2);
+
+        assert!(set.unregister_computed::<Doubled>());
+        assert!(set.try_get::<Doubled>().is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use indexmap::IndexMap;
+
+use crate::{slot::Slot, Type};
+
+/// An iterator over the [`Type`] and value of each slot in a
+/// [`SingletonSet`], created with [`SingletonSet::iter()`].
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::iter()`]: crate::SingletonSet::iter()
+pub struct Iter<'a>(pub(crate) indexmap::map::Iter<'a, Type, Slot>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a Type, &'a dyn Any);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(t, slot)| (t, slot.get()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over the [`Type`] and a mutable reference to the value of
+/// each slot in a [`SingletonSet`], created with
+/// [`SingletonSet::iter_mut()`].
+///
+/// Shared slots whose value is also held outside of the set are skipped,
+/// since their value can't be borrowed mutably, and so are pinned and
+/// sealed slots.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::iter_mut()`]: crate::SingletonSet::iter_mut()
+pub struct IterMut<'a>(
+    pub(crate) indexmap::map::IterMut<'a, Type, Slot>,
+    pub(crate) &'a IndexMap<Type, u64>,
+);
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a Type, &'a mut dyn Any);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sealed = self.1;
+        self.0
+            .by_ref()
+            .filter(|(t, _)| !sealed.contains_key(*t))
+            .find_map(|(t, slot)| slot.get_mut().map(|value| (t, value)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.0.size_hint().1)
+    }
+}
+
+/// The error returned by [`SingletonSet::insert_boxed()`] when the boxed
+/// value is not of the [`Type`] it was inserted as.
+///
+/// The rejected value can be recovered with [`.into_value()`].
+///
+/// [`SingletonSet::insert_boxed()`]: crate::SingletonSet::insert_boxed()
+/// [`.into_value()`]: Self::into_value()
+#[derive(Debug)]
+pub struct TypeMismatch {
+    pub(crate) expected: Type,
+    pub(crate) value: Box<dyn Any>,
+}
+
+impl TypeMismatch {
+    /// Returns the [`Type`] the value was inserted as.
+    pub fn expected(&self) -> Type {
+        self.expected
+    }
+
+    /// Returns the rejected value.
+    pub fn into_value(self) -> Box<dyn Any> {
+        self.value
+    }
+}
+
+impl Display for TypeMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "boxed value is not a {}", self.expected)
+    }
+}
+
+impl Error for TypeMismatch {}
@@ -0,0 +1,202 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use crate::{SingletonSet, Type};
+
+/// A type that can be built out of the values held by a [`SingletonSet`].
+///
+/// This is usually implemented with `#[derive(FromSingletonSet)]`, which is
+/// available with the `derive` feature. Each field of the struct is filled
+/// from the slot of the field's type.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use singletonset::{FromSingletonSet, SingletonSet};
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Config(&'static str);
+///
+/// #[derive(FromSingletonSet)]
+/// struct Context {
+///     #[singleton(clone)]
+///     config: Config,
+///     retries: u8,
+///     #[singleton(optional)]
+///     timeout: Option<u64>,
+/// }
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(Config("production"));
+/// set.insert(3u8);
+///
+/// let context: Context = set.extract().unwrap();
+///
+/// assert_eq!(context.config, Config("production"));
+/// assert_eq!(context.retries, 3);
+/// assert_eq!(context.timeout, None);
+///
+/// // The config was cloned, but the retry count was taken.
+/// assert!(set.contains::<Config>());
+/// assert!(!set.contains::<u8>());
+/// # }
+/// ```
+pub trait FromSingletonSet: Sized {
+    /// Builds a value out of the contents of `set`.
+    ///
+    /// If any required field is missing, any field would take a value the
+    /// set doesn't own outright, or two fields would take the same type's
+    /// value, the set is left untouched and the error names every such
+    /// field.
+    fn from_singleton_set(set: &mut SingletonSet) -> Result<Self, MissingFields>;
+}
+
+/// A type whose fields can each be inserted into a [`SingletonSet`].
+///
+/// This is usually implemented with `#[derive(IntoSingletonSet)]`, which is
+/// available with the `derive` feature.
+pub trait IntoSingletonSet: Sized {
+    /// Inserts each field into the slot of its type in `set`, replacing any
+    /// existing values.
+    fn insert_into_set(self, set: &mut SingletonSet);
+
+    /// Creates a new [`SingletonSet`] holding each field.
+    fn into_singleton_set(self) -> SingletonSet {
+        let mut set = SingletonSet::new();
+        self.insert_into_set(&mut set);
+        set
+    }
+}
+
+/// The error returned when a [`SingletonSet`] is missing the values needed
+/// to build a [`FromSingletonSet`] type, or can't give them up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingFields(
+    Vec<(&'static str, Type)>,
+    Vec<(&'static str, Type)>,
+    Vec<(&'static str, Type)>,
+);
+
+impl MissingFields {
+    /// Returns an iterator over the name and [`Type`] of each missing
+    /// field, in the order the fields were declared.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &Type)> {
+        self.0.iter().map(|(field, t)| (*field, t))
+    }
+
+    /// Returns an iterator over the name and [`Type`] of each field whose
+    /// value the set holds but doesn't own outright, such as a pinned value
+    /// or a shared value that other `Arc`s still point to.
+    pub fn not_owned(&self) -> impl Iterator<Item = (&'static str, &Type)> {
+        self.1.iter().map(|(field, t)| (*field, t))
+    }
+
+    /// Returns an iterator over the name and [`Type`] of each field that
+    /// would take the value of a type an earlier field already takes.
+    pub fn duplicated(&self) -> impl Iterator<Item = (&'static str, &Type)> {
+        self.2.iter().map(|(field, t)| (*field, t))
+    }
+}
+
+impl Display for MissingFields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lists = [
+            ("missing fields", &self.0),
+            ("fields not owned", &self.1),
+            ("fields taking a type already taken", &self.2),
+        ];
+        let lists = lists.iter().filter(|(_, fields)| !fields.is_empty());
+
+        for (i, (label, fields)) in lists.enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}: ", label)?;
+
+            for (i, (field, t)) in fields.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "`{}` ({})", field, t)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for MissingFields {}
+
+/// Helpers called by the code generated by the derive macros.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub fn check<T>(
+        set: &SingletonSet,
+        field: &'static str,
+        missing: &mut Vec<(&'static str, Type)>,
+    ) where
+        T: 'static,
+    {
+        if !set.contains::<T>() {
+            missing.push((field, Type::of::<T>()));
+        }
+    }
+
+    pub fn check_owned<T>(
+        set: &SingletonSet,
+        field: &'static str,
+        not_owned: &mut Vec<(&'static str, Type)>,
+    ) where
+        T: 'static,
+    {
+        if set.is_not_owned::<T>() {
+            not_owned.push((field, Type::of::<T>()));
+        }
+    }
+
+    pub fn check_unique<T>(
+        field: &'static str,
+        taken: &mut Vec<Type>,
+        duplicated: &mut Vec<(&'static str, Type)>,
+    ) where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if taken.contains(&t) {
+            duplicated.push((field, t));
+        } else {
+            taken.push(t);
+        }
+    }
+
+    pub fn missing(
+        missing: Vec<(&'static str, Type)>,
+        not_owned: Vec<(&'static str, Type)>,
+        duplicated: Vec<(&'static str, Type)>,
+    ) -> Result<(), MissingFields> {
+        if missing.is_empty() && not_owned.is_empty() && duplicated.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingFields(missing, not_owned, duplicated))
+        }
+    }
+
+    pub fn take<T>(set: &mut SingletonSet, field: &'static str) -> T
+    where
+        T: 'static,
+    {
+        set.remove().unwrap_or_else(|| {
+            panic!(
+                "field `{}` could not take its {}, which was taken by an earlier field",
+                field,
+                Type::of::<T>()
+            )
+        })
+    }
+}
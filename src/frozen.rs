@@ -0,0 +1,342 @@
+use std::any::{Any, TypeId};
+
+use indexmap::IndexMap;
+
+use crate::{
+    slot::{Slot, Storage},
+    SingletonSet, Type, Types, TypesInner,
+};
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for dyn std::any::Any {}
+    impl Sealed for dyn std::any::Any + Send + Sync {}
+}
+
+/// The trait object type that a [`FrozenSingletonSet`] stores its values
+/// as.
+///
+/// This is implemented for `dyn Any`, which can hold any `'static` value,
+/// and for `dyn Any + Send + Sync`, which makes the frozen set itself
+/// [`Send`] and [`Sync`]. It cannot be implemented outside of this crate.
+pub trait ErasedValue: private::Sealed + 'static {
+    #[doc(hidden)]
+    fn as_any(&self) -> &dyn Any;
+
+    #[doc(hidden)]
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl ErasedValue for dyn Any {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl ErasedValue for dyn Any + Send + Sync {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// An immutable [`SingletonSet`] that is optimized for lookups.
+///
+/// A frozen set is created with [`SingletonSet::freeze()`] once a set is
+/// fully populated. Its types are kept in a sorted array of [`TypeId`]s
+/// that is built once, at freeze time, and searched without hashing. The
+/// types, the index and the table of values each live in a single
+/// allocation, while each value stays in the box it had in the set.
+///
+/// Values can only be read from a frozen set. To change it, turn it back
+/// into a `SingletonSet` with [`.thaw()`].
+///
+/// Since a `SingletonSet` can hold values of any type, a set created by
+/// [`SingletonSet::freeze()`] is neither [`Send`] nor [`Sync`]. A
+/// [`SyncFrozenSingletonSet`] is both, and is created with a
+/// [`FrozenBuilder`] that only accepts values that are themselves `Send`
+/// and `Sync`.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::SingletonSet;
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(42u8);
+/// set.insert("Hello".to_string());
+///
+/// let frozen = set.freeze();
+/// assert_eq!(frozen.get::<u8>(), &42);
+/// assert_eq!(frozen.try_get::<u16>(), None);
+///
+/// let mut set = frozen.thaw();
+/// set.insert(7u16);
+/// assert_eq!(set.len(), 3);
+/// ```
+///
+/// [`.thaw()`]: Self::thaw()
+#[derive(Debug)]
+pub struct FrozenSingletonSet<A: ?Sized + ErasedValue = dyn Any> {
+    types: Box<[Type]>,
+    values: Box<[(Box<A>, Storage)]>,
+    /// The position of each type in `types`, sorted by [`TypeId`].
+    index: Box<[(TypeId, usize)]>,
+}
+
+/// A [`FrozenSingletonSet`] that can be shared between threads.
+pub type SyncFrozenSingletonSet = FrozenSingletonSet<dyn Any + Send + Sync>;
+
+impl FrozenSingletonSet {
+    pub(crate) fn from_slots(slots: IndexMap<Type, Slot>) -> Self {
+        Self::build(
+            slots
+                .into_iter()
+                .map(|(t, slot)| (t, slot.into_parts()))
+                .collect(),
+        )
+    }
+}
+
+impl<A> FrozenSingletonSet<A>
+where
+    A: ?Sized + ErasedValue,
+{
+    /// Creates a [`FrozenBuilder`] for a frozen set with values stored as
+    /// `A`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SyncFrozenSingletonSet;
+    ///
+    /// let frozen = SyncFrozenSingletonSet::builder()
+    ///     .with(42u8)
+    ///     .with("Hello".to_string())
+    ///     .freeze();
+    ///
+    /// std::thread::scope(|s| {
+    ///     s.spawn(|| assert_eq!(frozen.get::<u8>(), &42));
+    /// });
+    /// ```
+    pub fn builder() -> FrozenBuilder<A> {
+        FrozenBuilder(IndexMap::new())
+    }
+
+    fn build(entries: IndexMap<Type, (Box<A>, Storage)>) -> Self {
+        let (types, values): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+
+        let mut index: Box<[_]> = types
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.to_id(), i))
+            .collect();
+        index.sort_unstable_by_key(|(id, _)| *id);
+
+        FrozenSingletonSet {
+            types: types.into(),
+            values: values.into(),
+            index,
+        }
+    }
+
+    fn position(&self, t: &Type) -> Option<usize> {
+        self.index
+            .binary_search_by_key(t.as_id(), |(id, _)| *id)
+            .ok()
+            .map(|i| self.index[i].1)
+    }
+
+    /// Returns the number of elements the set holds.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Returns true if the type is represented in the set.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.contains_type(&Type::of::<T>())
+    }
+
+    /// Returns true if the supplied [`Type`] is represented in the set.
+    pub fn contains_type(&self, t: &Type) -> bool {
+        self.position(t).is_some()
+    }
+
+    /// Returns an immutable reference to the value of the specified type.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if there is no value for the given type. If this
+    /// is not acceptable, use [`.try_get()`].
+    ///
+    /// [`.try_get()`]: Self::try_get()
+    pub fn get<T>(&self) -> &T
+    where
+        T: 'static,
+    {
+        self.try_get()
+            .expect(".try_get() should be used if the slot might be empty")
+    }
+
+    /// Returns an immutable reference to the value of the specified type,
+    /// if it exists.
+    pub fn try_get<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        self.position(&Type::of::<T>()).and_then(|i| {
+            let (raw, storage) = &self.values[i];
+            storage.get(raw.as_any()).downcast_ref()
+        })
+    }
+
+    /// Returns an iterator that visits each [`Type`] in the set, in the
+    /// order they were inserted before the set was frozen.
+    pub fn types(&self) -> Types<'_> {
+        Types(TypesInner::Slice(self.types.iter()))
+    }
+
+    /// Converts the frozen set back into a mutable [`SingletonSet`],
+    /// keeping the order of its types.
+    pub fn thaw(self) -> SingletonSet {
+        let slots = self
+            .values
+            .into_vec()
+            .into_iter()
+            .map(|(raw, storage)| Slot::from_parts(raw.into_any(), storage));
+
+        SingletonSet(
+            self.types.iter().copied().zip(slots).collect(),
+            Default::default(),
+        )
+    }
+}
+
+/// A builder for a [`FrozenSingletonSet`], created with
+/// [`FrozenSingletonSet::builder()`].
+///
+/// Like a [`SingletonSet`], the builder keeps only the last value inserted
+/// for each type.
+pub struct FrozenBuilder<A: ?Sized + ErasedValue = dyn Any>(IndexMap<Type, (Box<A>, Storage)>);
+
+impl FrozenBuilder {
+    /// Adds a value to the set being built.
+    pub fn with<T>(mut self, value: T) -> Self
+    where
+        T: 'static,
+    {
+        self.0
+            .insert(Type::of::<T>(), (Box::new(value), Storage::Owned));
+        self
+    }
+}
+
+impl FrozenBuilder<dyn Any + Send + Sync> {
+    /// Adds a value to the set being built.
+    pub fn with<T>(mut self, value: T) -> Self
+    where
+        T: 'static + Send + Sync,
+    {
+        self.0
+            .insert(Type::of::<T>(), (Box::new(value), Storage::Owned));
+        self
+    }
+}
+
+impl<A> FrozenBuilder<A>
+where
+    A: ?Sized + ErasedValue,
+{
+    /// Builds the [`FrozenSingletonSet`].
+    pub fn freeze(self) -> FrozenSingletonSet<A> {
+        FrozenSingletonSet::build(self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frozen_set_finds_every_type() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert(2u16);
+        set.insert(3u32);
+        set.insert(4u64);
+        set.insert("five");
+
+        let frozen = set.freeze();
+
+        assert_eq!(frozen.len(), 5);
+        assert_eq!(frozen.get::<u8>(), &1);
+        assert_eq!(frozen.get::<u16>(), &2);
+        assert_eq!(frozen.get::<u32>(), &3);
+        assert_eq!(frozen.get::<u64>(), &4);
+        assert_eq!(frozen.get::<&str>(), &"five");
+        assert_eq!(frozen.try_get::<i8>(), None);
+        assert!(!frozen.contains::<String>());
+    }
+
+    #[test]
+    fn thaw_keeps_the_order_of_types() {
+        let mut set = SingletonSet::new();
+        set.insert(3u32);
+        set.insert(1u8);
+        set.insert(2u16);
+
+        let before: Vec<_> = set.types().copied().collect();
+        let frozen = set.freeze();
+        assert!(frozen.types().eq(before.iter()));
+
+        let set = frozen.thaw();
+        assert!(set.types().eq(before.iter()));
+        assert_eq!(set.get::<u8>(), &1);
+    }
+
+    #[test]
+    fn shared_slots_stay_shared() {
+        let mut set = SingletonSet::new();
+        set.insert_shared(1u8);
+
+        let frozen = set.freeze();
+        assert_eq!(frozen.get::<u8>(), &1);
+
+        let set = frozen.thaw();
+        assert!(set.is_shared::<u8>());
+        assert_eq!(set.get_arc::<u8>().as_deref(), Some(&1));
+    }
+
+    #[test]
+    fn sync_frozen_set_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let frozen = SyncFrozenSingletonSet::builder()
+            .with(1u8)
+            .with(2u8)
+            .freeze();
+
+        assert_send_sync(&frozen);
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen.get::<u8>(), &2);
+        assert_eq!(frozen.thaw().get::<u8>(), &2);
+    }
+}
@@ -0,0 +1,509 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    ffi::OsString,
+    fmt::{Display, Formatter},
+    mem::size_of,
+    path::PathBuf,
+    rc::Rc,
+    sync::Arc,
+};
+
+use crate::{slot::Slot, SlotKind, Type};
+
+/// Measures the heap memory owned by a value, not counting the value
+/// itself.
+///
+/// The measurement is approximate. It counts the capacity of each
+/// allocation the value owns, along with the heap memory owned by anything
+/// stored in those allocations, but not the bookkeeping of the allocator.
+/// The sizes of hash tables and trees are estimated from their length or
+/// capacity. Values behind [`Rc`] and [`Arc`] are counted in full by every
+/// pointer to them.
+///
+/// Values inserted into a [`SingletonSet`] with
+/// [`.insert_measured()`] are measured by [`.memory_usage()`].
+///
+/// # Example
+///
+/// ```
+/// use singletonset::HeapSize;
+///
+/// struct Document {
+///     title: String,
+///     lines: Vec<String>,
+/// }
+///
+/// impl HeapSize for Document {
+///     fn heap_size(&self) -> usize {
+///         self.title.heap_size() + self.lines.heap_size()
+///     }
+/// }
+///
+/// let mut lines = Vec::with_capacity(2);
+/// lines.push(String::with_capacity(10));
+/// let doc = Document { title: String::with_capacity(4), lines };
+///
+/// assert_eq!(doc.heap_size(), 4 + 2 * std::mem::size_of::<String>() + 10);
+/// ```
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`.insert_measured()`]: crate::SingletonSet::insert_measured()
+/// [`.memory_usage()`]: crate::SingletonSet::memory_usage()
+pub trait HeapSize {
+    /// Returns the number of bytes of heap memory owned by the value.
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! impl_heap_size_none {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl HeapSize for $t {
+                #[inline]
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )+
+    };
+}
+
+impl_heap_size_none!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    &'static str,
+    std::time::Duration,
+    std::time::Instant,
+    std::time::SystemTime,
+);
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for OsString {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for PathBuf {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for Box<str> {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> HeapSize for Box<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T> HeapSize for Box<[T]>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        size_of::<T>() * self.len() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T> HeapSize for Rc<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        2 * size_of::<usize>() + size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T> HeapSize for Arc<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        2 * size_of::<usize>() + size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T> HeapSize for Option<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_size)
+    }
+}
+
+impl<T> HeapSize for Cow<'static, T>
+where
+    T: ToOwned + ?Sized,
+    T::Owned: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        match self {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(owned) => owned.heap_size(),
+        }
+    }
+}
+
+impl<T> HeapSize for Vec<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        size_of::<T>() * self.capacity() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T> HeapSize for VecDeque<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        size_of::<T>() * self.capacity() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T, const N: usize> HeapSize for [T; N]
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        self.iter().map(HeapSize::heap_size).sum()
+    }
+}
+
+impl<K, V, S> HeapSize for HashMap<K, V, S>
+where
+    K: HeapSize,
+    V: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        // Each bucket of the table holds an entry and one control byte.
+        (size_of::<(K, V)>() + 1) * self.capacity()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<T, S> HeapSize for HashSet<T, S>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        (size_of::<T>() + 1) * self.capacity() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<K, V> HeapSize for BTreeMap<K, V>
+where
+    K: HeapSize,
+    V: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        size_of::<(K, V)>() * self.len()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<T> HeapSize for BTreeSet<T>
+where
+    T: HeapSize,
+{
+    fn heap_size(&self) -> usize {
+        size_of::<T>() * self.len() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+macro_rules! impl_heap_size_tuple {
+    ($($name:ident),+) => {
+        impl<$($name),+> HeapSize for ($($name,)+)
+        where
+            $($name: HeapSize,)+
+        {
+            #[allow(non_snake_case)]
+            fn heap_size(&self) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.heap_size())+
+            }
+        }
+    };
+}
+
+impl_heap_size_tuple!(A);
+impl_heap_size_tuple!(A, B);
+impl_heap_size_tuple!(A, B, C);
+impl_heap_size_tuple!(A, B, C, D);
+impl_heap_size_tuple!(A, B, C, D, E);
+impl_heap_size_tuple!(A, B, C, D, E, F);
+
+/// A function that measures the heap memory of a type-erased value.
+pub(crate) type MeasureFn = fn(&dyn Any) -> usize;
+
+pub(crate) fn measure<T>(value: &dyn Any) -> usize
+where
+    T: HeapSize + 'static,
+{
+    value.downcast_ref::<T>().map_or(0, HeapSize::heap_size)
+}
+
+/// The memory the set's index uses for each slot, whether or not the slot
+/// is filled: the entry itself, along with its hash, and the hash table's
+/// index and control byte.
+pub(crate) const INDEX_ENTRY_SIZE: usize =
+    size_of::<usize>() + size_of::<Type>() + size_of::<Slot>() + size_of::<usize>() + 1;
+
+/// Returns the memory used to reach the value of a slot, besides the
+/// allocation that holds the value itself.
+pub(crate) fn box_overhead(kind: SlotKind) -> usize {
+    match kind {
+        SlotKind::Owned => 0,
+        // The outer box holds the fat pointer of the inner one.
+        SlotKind::Unsized => 2 * size_of::<usize>(),
+        // The box holds the `Arc`, and the `Arc`'s allocation starts with
+        // its reference counts.
+        SlotKind::Shared => 3 * size_of::<usize>(),
+        // The outer box holds the pointer of the `Pin<Box<T>>`.
+        SlotKind::Pinned => size_of::<usize>(),
+    }
+}
+
+/// The memory used by one slot of a [`SingletonSet`], as reported by
+/// [`SingletonSet::memory_usage()`].
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::memory_usage()`]: crate::SingletonSet::memory_usage()
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotMemory {
+    pub(crate) t: Type,
+    pub(crate) heap: Option<usize>,
+    pub(crate) overhead: usize,
+}
+
+impl SlotMemory {
+    /// Returns the [`Type`] of the slot.
+    pub fn to_type(&self) -> Type {
+        self.t
+    }
+
+    /// Returns the size of the value itself, or [`None`] if its type is
+    /// unsized.
+    pub fn value_size(&self) -> Option<usize> {
+        self.t.try_size()
+    }
+
+    /// Returns the heap memory owned by the value, or [`None`] if the
+    /// value wasn't inserted with [`SingletonSet::insert_measured()`].
+    ///
+    /// [`SingletonSet::insert_measured()`]: crate::SingletonSet::insert_measured()
+    pub fn heap_size(&self) -> Option<usize> {
+        self.heap
+    }
+
+    /// Returns the memory the set uses to find and reach the value: its
+    /// entry in the set's index, and any boxes or reference counts around
+    /// the value.
+    pub fn overhead(&self) -> usize {
+        self.overhead
+    }
+
+    /// Returns the sum of the value's size, its heap memory and the
+    /// overhead, counting unknown sizes as zero.
+    pub fn total(&self) -> usize {
+        self.value_size().unwrap_or(0) + self.heap.unwrap_or(0) + self.overhead
+    }
+}
+
+/// A breakdown of the memory used by a [`SingletonSet`], created with
+/// [`SingletonSet::memory_usage()`].
+///
+/// The report implements [`Display`], which prints one line per slot
+/// followed by the totals.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::{SingletonSet, Type};
+///
+/// let mut set = SingletonSet::new();
+/// set.insert_measured(String::with_capacity(1024));
+/// set.insert(7u8);
+///
+/// let usage = set.memory_usage();
+/// let string = usage.get(&Type::of::<String>()).unwrap();
+/// assert_eq!(string.heap_size(), Some(1024));
+/// assert_eq!(usage.get(&Type::of::<u8>()).unwrap().heap_size(), None);
+/// assert_eq!(usage.unmeasured_count(), 1);
+/// assert!(usage.total() > 1024);
+/// ```
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::memory_usage()`]: crate::SingletonSet::memory_usage()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub(crate) slots: Vec<SlotMemory>,
+    pub(crate) spare_capacity: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the memory used by each slot, in the order of the set.
+    pub fn slots(&self) -> &[SlotMemory] {
+        &self.slots
+    }
+
+    /// Returns the memory used by the slot of the given [`Type`].
+    pub fn get(&self, t: &Type) -> Option<&SlotMemory> {
+        self.slots.iter().find(|slot| slot.t == *t)
+    }
+
+    /// Returns the memory the set's index has reserved for slots that
+    /// aren't filled yet.
+    pub fn spare_capacity(&self) -> usize {
+        self.spare_capacity
+    }
+
+    /// Returns the number of slots whose heap memory is unknown, because
+    /// their values weren't inserted with
+    /// [`SingletonSet::insert_measured()`].
+    ///
+    /// [`SingletonSet::insert_measured()`]: crate::SingletonSet::insert_measured()
+    pub fn unmeasured_count(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.heap.is_none()).count()
+    }
+
+    /// Returns the total memory used by the set, including the spare
+    /// capacity of its index, and counting unknown sizes as zero.
+    pub fn total(&self) -> usize {
+        self.slots.iter().map(SlotMemory::total).sum::<usize>() + self.spare_capacity
+    }
+}
+
+impl Display for MemoryUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        fn known(size: Option<usize>) -> String {
+            size.map_or_else(|| "?".to_string(), |size| size.to_string())
+        }
+
+        writeln!(
+            f,
+            "{:>8} {:>10} {:>8} {:>10}  type",
+            "value", "heap", "overhead", "total"
+        )?;
+
+        for slot in &self.slots {
+            writeln!(
+                f,
+                "{:>8} {:>10} {:>8} {:>10}  {}",
+                known(slot.value_size()),
+                known(slot.heap),
+                slot.overhead,
+                slot.total(),
+                slot.t
+            )?;
+        }
+
+        writeln!(
+            f,
+            "{:>8} {:>10} {:>8} {:>10}  spare capacity",
+            "", "", self.spare_capacity, self.spare_capacity
+        )?;
+        write!(
+            f,
+            "{:>8} {:>10} {:>8} {:>10}  total ({} slots)",
+            "",
+            "",
+            "",
+            self.total(),
+            self.slots.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SingletonSet;
+
+    #[test]
+    fn std_types_report_their_heap_memory() {
+        assert_eq!(5u32.heap_size(), 0);
+        assert_eq!(String::with_capacity(16).heap_size(), 16);
+        assert_eq!(
+            vec![String::with_capacity(3)].heap_size(),
+            size_of::<String>() + 3
+        );
+        assert_eq!(Some(Box::new(7u64)).heap_size(), 8);
+        assert_eq!((String::with_capacity(2), 1u8).heap_size(), 2);
+        assert_eq!(Cow::<'static, str>::Borrowed("static").heap_size(), 0);
+
+        let mut map = HashMap::with_capacity(4);
+        map.insert(1u32, String::with_capacity(5));
+        assert!(map.heap_size() >= 4 * (size_of::<(u32, String)>() + 1) + 5);
+    }
+
+    #[test]
+    fn memory_usage_includes_overhead() {
+        let mut set = SingletonSet::with_capacity(4);
+        set.insert_measured(vec![0u64; 8]);
+        set.insert_shared(1u32);
+        set.insert_unsized::<str>("unsized".into());
+
+        let usage = set.memory_usage();
+        assert_eq!(usage.slots().len(), 3);
+
+        let vec = usage.get(&Type::of::<Vec<u64>>()).unwrap();
+        assert_eq!(vec.heap_size(), Some(64));
+        assert_eq!(vec.overhead(), INDEX_ENTRY_SIZE);
+        assert_eq!(vec.total(), size_of::<Vec<u64>>() + 64 + INDEX_ENTRY_SIZE);
+
+        let shared = usage.get(&Type::of::<u32>()).unwrap();
+        assert_eq!(
+            shared.overhead(),
+            INDEX_ENTRY_SIZE + box_overhead(SlotKind::Shared)
+        );
+
+        let boxed_str = usage.get(&Type::of_unsized::<str>()).unwrap();
+        assert_eq!(boxed_str.value_size(), None);
+        assert_eq!(usage.unmeasured_count(), 2);
+
+        assert_eq!(
+            usage.spare_capacity(),
+            (set.capacity() - 3) * INDEX_ENTRY_SIZE
+        );
+        assert!(usage.to_string().ends_with("total (3 slots)"));
+    }
+}
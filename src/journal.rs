@@ -0,0 +1,295 @@
+use std::{any::Any, collections::VecDeque, ops::Deref};
+
+use crate::{slot::Slot, SingletonSet, Type};
+
+/// One change recorded in the journal of a [`JournaledSingletonSet`].
+///
+/// `other` holds the slot that is not currently in the set, along with the
+/// index it had. Before the change is undone, that is the slot the change
+/// replaced or removed; after it is undone, it is the slot the change
+/// inserted. Undoing and redoing a change both swap it with the set's slot.
+#[derive(Debug)]
+struct Change {
+    t: Type,
+    other: Option<(usize, Slot)>,
+}
+
+/// A [`SingletonSet`] that records every insertion, replacement and
+/// removal in a bounded journal, so they can be undone and redone.
+///
+/// A journaled set is made with [`SingletonSet::journaled()`]. The set can
+/// be read through [`Deref`], but can only be changed through the methods
+/// of this type, so that no change goes unrecorded. Since the journal
+/// keeps replaced and removed values in order to restore them, those
+/// values are not returned to the caller.
+///
+/// Once the journal holds as many changes as its capacity allows, the
+/// oldest change is forgotten each time a new one is recorded. Recording a
+/// change also forgets every change that was undone and not yet redone.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::{SingletonSet, Type};
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(1u8);
+/// set.insert("two");
+///
+/// let mut set = set.journaled(100);
+/// set.remove::<u8>();
+/// set.insert("three");
+/// assert!(set.types().eq([Type::of::<&str>()].iter()));
+///
+/// set.undo();
+/// set.undo();
+/// assert!(set.types().eq([Type::of::<u8>(), Type::of::<&str>()].iter()));
+/// assert_eq!(set.get::<&str>(), &"two");
+///
+/// set.redo();
+/// assert!(!set.contains::<u8>());
+/// ```
+///
+/// [`SingletonSet::journaled()`]: crate::SingletonSet::journaled()
+#[derive(Debug)]
+pub struct JournaledSingletonSet {
+    set: SingletonSet,
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    capacity: usize,
+}
+
+impl JournaledSingletonSet {
+    pub(crate) fn new(set: SingletonSet, capacity: usize) -> Self {
+        JournaledSingletonSet {
+            set,
+            undo: VecDeque::with_capacity(capacity),
+            redo: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the number of changes the journal can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of changes that can be undone.
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Returns the number of changes that can be redone.
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Inserts a value into the inferred type's slot, and records the
+    /// change.
+    ///
+    /// Returns true if the slot already held a value, which is kept in the
+    /// journal so the insertion can be undone. If the slot is sealed, or
+    /// the value is rejected by the type's validator, the value is dropped,
+    /// nothing is recorded, and false is returned.
+    pub fn insert<T>(&mut self, value: T) -> bool
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if self.set.1.check_unsealed(&t).is_err() || !self.set.1.admit(&t, &value) {
+            return false;
+        }
+
+        let index = self.set.0.get_index_of(&t).unwrap_or(self.set.len());
+
+        self.record(t, Some((index, Slot::new(value))))
+    }
+
+    /// Removes the value from the inferred type's slot, and records the
+    /// change.
+    ///
+    /// Returns true if the slot held a value, which is kept in the journal
+    /// so the removal can be undone. Nothing is recorded if the slot was
+    /// empty or is sealed.
+    pub fn remove<T>(&mut self) -> bool
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if !self.set.contains_type(&t) || self.set.1.check_unsealed(&t).is_err() {
+            return false;
+        }
+
+        self.record(t, None)
+    }
+
+    fn record(&mut self, t: Type, slot: Option<(usize, Slot)>) -> bool {
+        let other = self.set.swap_slot(t, slot);
+        let replaced = other.is_some();
+
+        self.redo.clear();
+        if self.capacity > 0 {
+            if self.undo.len() == self.capacity {
+                self.undo.pop_front();
+            }
+            self.undo.push_back(Change { t, other });
+        }
+
+        replaced
+    }
+
+    /// Undoes the most recent change that hasn't been undone, restoring the
+    /// slot it changed to its previous value and position.
+    ///
+    /// The restored value was validated when it was first inserted, so it
+    /// isn't validated again. Otherwise registering a validator could leave
+    /// a change that can be neither undone nor redone.
+    ///
+    /// Returns false if there was no change to undo, or if the slot it
+    /// changed is sealed, in which case the change stays in the journal.
+    pub fn undo(&mut self) -> bool {
+        if !self.undo.back().map_or(false, |c| self.is_unsealed(&c.t)) {
+            return false;
+        }
+
+        match self.undo.pop_back() {
+            Some(mut change) => {
+                change.other = self.set.swap_slot(change.t, change.other.take());
+                self.redo.push(change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redoes the most recently undone change.
+    ///
+    /// Like [`.undo()`], this doesn't validate the value it restores.
+    ///
+    /// Returns false if there was no change to redo, or if the slot it
+    /// changes is sealed.
+    ///
+    /// [`.undo()`]: Self::undo()
+    pub fn redo(&mut self) -> bool {
+        if !self.redo.last().map_or(false, |c| self.is_unsealed(&c.t)) {
+            return false;
+        }
+
+        match self.redo.pop() {
+            Some(mut change) => {
+                change.other = self.set.swap_slot(change.t, change.other.take());
+                self.undo.push_back(change);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_unsealed(&self, t: &Type) -> bool {
+        self.set.1.check_unsealed(t).is_ok()
+    }
+
+    /// Forgets every recorded change, keeping the set as it is.
+    pub fn clear_journal(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Returns the set, and drops the journal.
+    pub fn into_inner(self) -> SingletonSet {
+        self.set
+    }
+
+    /// Returns the [`Type`] of each change that can be undone, from oldest
+    /// to newest, along with the value the change left in the slot, or
+    /// [`None`] if it removed the value.
+    #[cfg_attr(not(feature = "replay"), allow(dead_code))]
+    pub(crate) fn applied(&self) -> impl Iterator<Item = (Type, Option<&dyn Any>)> {
+        self.undo.iter().enumerate().map(move |(i, change)| {
+            // The value a change left behind is the one the next change to
+            // the same slot replaced, or the one still in the set.
+            let after = match self.undo.iter().skip(i + 1).find(|c| c.t == change.t) {
+                Some(later) => later.other.as_ref().map(|(_, slot)| slot.get()),
+                None => self.set.0.get(&change.t).map(Slot::get),
+            };
+            (change.t, after)
+        })
+    }
+}
+
+impl Deref for JournaledSingletonSet {
+    type Target = SingletonSet;
+
+    fn deref(&self) -> &Self::Target {
+        &self.set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SingletonSet, Type};
+
+    #[test]
+    fn undo_and_redo_restore_values_and_order() {
+        let mut set = SingletonSet::new().journaled(10);
+        set.insert(1u8);
+        set.insert(2u16);
+        set.insert(3u32);
+        assert!(set.insert(20u16));
+        assert!(set.remove::<u8>());
+        assert!(!set.remove::<u8>());
+        assert_eq!(set.undo_len(), 5);
+
+        assert!(set.undo());
+        assert!(set.undo());
+        assert_eq!(set.get::<u16>(), &2);
+        assert!(set
+            .types()
+            .eq([Type::of::<u8>(), Type::of::<u16>(), Type::of::<u32>()].iter()));
+
+        assert!(set.redo());
+        assert_eq!(set.get::<u16>(), &20);
+        assert_eq!(set.redo_len(), 1);
+
+        set.insert("new");
+        assert_eq!(set.redo_len(), 0);
+        assert!(!set.redo());
+
+        while set.undo() {}
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn the_journal_forgets_its_oldest_changes() {
+        let mut set = SingletonSet::new().journaled(2);
+        set.insert(1u8);
+        set.insert(2u8);
+        set.insert(3u8);
+
+        assert!(set.undo());
+        assert!(set.undo());
+        assert!(!set.undo());
+        assert_eq!(set.get::<u8>(), &1);
+
+        let mut set = SingletonSet::new().journaled(0);
+        set.insert(1u8);
+        assert!(!set.undo());
+        assert_eq!(set.into_inner().get::<u8>(), &1);
+    }
+
+    #[test]
+    fn the_journal_leaves_sealed_slots_alone() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        let token = set.seal::<u8>().unwrap();
+
+        let mut set = set.journaled(10);
+        assert!(!set.insert(2u8));
+        assert!(!set.remove::<u8>());
+        assert_eq!(set.undo_len(), 0);
+        assert_eq!(set.get::<u8>(), &1);
+
+        let mut set = set.into_inner();
+        assert!(set.unseal::<u8>(&token));
+    }
+}
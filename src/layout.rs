@@ -0,0 +1,140 @@
+use std::fmt::{Display, Formatter};
+
+use crate::Type;
+
+/// A summary of the memory layout of the values held by a
+/// [`SingletonSet`], created with [`SingletonSet::layout_report()`].
+///
+/// Each slot of the set holds its value in its own heap allocation, so the
+/// totals describe the values themselves and not the bookkeeping the set
+/// needs to find them.
+///
+/// The report implements [`Display`], which prints one line per slot
+/// followed by the totals.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::layout_report()`]: crate::SingletonSet::layout_report()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutReport {
+    slots: Vec<Type>,
+}
+
+impl LayoutReport {
+    pub(crate) fn new(slots: Vec<Type>) -> Self {
+        LayoutReport { slots }
+    }
+
+    /// Returns the [`Type`] of each slot, in the order of the set.
+    pub fn slots(&self) -> &[Type] {
+        &self.slots
+    }
+
+    /// Returns the sum of the sizes of every sized slot's type, in bytes.
+    pub fn total_size(&self) -> usize {
+        self.slots.iter().filter_map(Type::try_size).sum()
+    }
+
+    /// Returns the largest alignment of any sized slot's type, in bytes, or
+    /// 1 if the set holds no sized values.
+    pub fn max_align(&self) -> usize {
+        self.slots
+            .iter()
+            .filter_map(Type::try_align)
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Returns the number of slots whose type is unsized, and so is not
+    /// counted by [`.total_size()`] or [`.max_align()`].
+    ///
+    /// [`.total_size()`]: Self::total_size()
+    /// [`.max_align()`]: Self::max_align()
+    pub fn unsized_count(&self) -> usize {
+        self.slots.iter().filter(|t| t.try_size().is_none()).count()
+    }
+
+    /// Returns the number of slots whose type needs to be dropped.
+    pub fn needs_drop_count(&self) -> usize {
+        self.slots.iter().filter(|t| t.needs_drop()).count()
+    }
+}
+
+impl Display for LayoutReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:>8} {:>6} {:>5}  type", "size", "align", "drop")?;
+
+        for t in &self.slots {
+            let (size, align) = match (t.try_size(), t.try_align()) {
+                (Some(size), Some(align)) => (size.to_string(), align.to_string()),
+                _ => ("?".to_string(), "?".to_string()),
+            };
+            writeln!(
+                f,
+                "{:>8} {:>6} {:>5}  {}",
+                size,
+                align,
+                if t.needs_drop() { "yes" } else { "no" },
+                t
+            )?;
+        }
+
+        write!(
+            f,
+            "{:>8} {:>6} {:>5}  total ({} slots)",
+            self.total_size(),
+            self.max_align(),
+            self.needs_drop_count(),
+            self.slots.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SingletonSet;
+
+    #[test]
+    fn type_records_layout() {
+        let t = Type::of::<u64>();
+        assert_eq!(t.size(), std::mem::size_of::<u64>());
+        assert_eq!(t.align(), std::mem::align_of::<u64>());
+        assert!(!t.needs_drop());
+
+        let t = Type::of::<String>();
+        assert_eq!(t.size(), std::mem::size_of::<String>());
+        assert!(t.needs_drop());
+
+        assert_eq!(Type::of::<()>().try_size(), Some(0));
+
+        let t = Type::of_unsized::<str>();
+        assert_eq!(t, Type::of_unsized::<str>());
+        assert_eq!(t.try_size(), None);
+        assert_eq!(t.try_align(), None);
+        assert_eq!(Type::of_unsized::<u64>(), Type::of::<u64>());
+        assert_eq!(Type::of_unsized::<u64>().try_size(), None);
+    }
+
+    #[test]
+    fn layout_report_totals_slots() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert(2u32);
+        set.insert("three".to_string());
+        set.insert_unsized::<[u8]>(vec![4; 4].into());
+
+        let report = set.layout_report();
+
+        assert_eq!(report.slots().len(), 4);
+        assert_eq!(report.unsized_count(), 1);
+        assert_eq!(report.total_size(), 1 + 4 + std::mem::size_of::<String>());
+        assert_eq!(report.max_align(), std::mem::align_of::<String>());
+        assert_eq!(report.needs_drop_count(), 2);
+
+        let text = report.to_string();
+        assert_eq!(text.lines().count(), 6);
+        assert!(text.lines().last().unwrap().ends_with("total (4 slots)"));
+
+        assert_eq!(SingletonSet::new().layout_report().max_align(), 1);
+    }
+}
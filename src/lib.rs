@@ -2,13 +2,121 @@
 #![deny(missing_docs)]
 
 use std::{
+    alloc::Layout,
     any::{Any, TypeId},
     fmt::{Display, Formatter},
     hash::Hasher,
+    pin::Pin,
+    sync::{Arc, Weak},
 };
 
 use indexmap::IndexMap;
 pub use indexmap::TryReserveError;
+use slot::{Slot, Storage};
+
+mod bundle;
+mod computed;
+mod dynamic;
+mod extract;
+mod frozen;
+mod heap_size;
+mod journal;
+mod layout;
+mod multiton;
+mod name;
+mod persistent;
+#[cfg(feature = "inventory")]
+pub mod registry;
+#[cfg(feature = "replay")]
+mod replay;
+mod schema;
+mod seal;
+mod slot;
+mod stable_key;
+#[cfg(feature = "stats")]
+mod stats;
+pub mod type_name;
+pub mod type_set;
+mod validate;
+mod view;
+
+pub use bundle::{Bundle, BundleRef, MissingTypes};
+pub use dynamic::{Iter, IterMut, TypeMismatch};
+#[doc(hidden)]
+pub use extract::__private;
+pub use extract::{FromSingletonSet, IntoSingletonSet, MissingFields};
+pub use frozen::{ErasedValue, FrozenBuilder, FrozenSingletonSet, SyncFrozenSingletonSet};
+pub use heap_size::{HeapSize, MemoryUsage, SlotMemory};
+pub use journal::JournaledSingletonSet;
+pub use layout::LayoutReport;
+pub use multiton::MultitonSet;
+pub use name::{FindByNameError, FormattedSlot};
+pub use persistent::PersistentSingletonSet;
+#[cfg(feature = "replay")]
+pub use replay::{Mutation, ReplayError, SerdeRegistry};
+pub use schema::{Schema, SchemaEntry, SchemaReport};
+pub use seal::{Sealed, UnsealToken};
+#[cfg(feature = "derive")]
+pub use singletonset_derive::{FromSingletonSet, IntoSingletonSet};
+pub use slot::SlotKind;
+pub use stable_key::{ParseStableKeyError, StableKey, StableKeyConflict, StableTypeKey};
+#[cfg(feature = "stats")]
+pub use stats::{Clock, SlotStats, StatsReport, SystemClock};
+pub use type_name::TypeName;
+pub use type_set::TypeSet;
+pub use validate::{ValidationError, ValidationFailure, ValidationPolicy, ValidationReport};
+pub use view::{NotAllowed, SingletonSetView, SingletonSetViewMut};
+
+/// The panic message for mutable access to a slot that is pinned, or that
+/// is shared and whose value is also held outside of the set.
+const NOT_MUT: &str =
+    "the slot can't be borrowed mutably, because it is pinned or its `Arc` is held elsewhere";
+
+/// The panic message for borrowing a value of a sized type from a slot that
+/// was filled by [`SingletonSet::insert_unsized()`].
+const UNSIZED: &str = "the slot was filled by .insert_unsized(), so .get_unsized() must be used";
+
+/// The panic message for a panicking lookup of a slot that is empty.
+const MISSING: &str = ".try_as_ref() or .as_mut() should be used if the slot might be empty";
+
+/// Emits a `tracing` event about the slot of a [`Type`], if the `tracing`
+/// feature is enabled.
+macro_rules! trace_slot {
+    ($level:ident, $message:literal, $t:expr) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!(target: "singletonset", type_name = $t.as_str(), $message);
+        #[cfg(not(feature = "tracing"))]
+        let _ = &$t;
+    };
+}
+
+/// Panics with `message` about the slot of `t`, after emitting a `tracing`
+/// event if the `tracing` feature is enabled.
+#[cold]
+#[track_caller]
+fn slot_panic(t: &Type, message: &str) -> ! {
+    trace_slot!(error, "panicking lookup", t);
+    panic!("{}", message)
+}
+
+/// Wraps the initialization of the slot of `t` so that, if the `tracing`
+/// feature is enabled, it runs inside a span naming the type.
+fn traced<R>(t: Type, init: impl FnOnce() -> R) -> impl FnOnce() -> R {
+    #[cfg(not(feature = "tracing"))]
+    let _ = t;
+
+    move || {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            target: "singletonset",
+            "initialize",
+            type_name = t.as_str()
+        )
+        .entered();
+
+        init()
+    }
+}
 
 /// A hash map that uses the value's type as its key.
 ///
@@ -16,7 +124,165 @@ pub use indexmap::TryReserveError;
 /// of any data type it holds. It ensures there is only one instance of any
 /// type, similar to a Singleton, without requiring a global scope.
 #[derive(Debug, Default)]
-pub struct SingletonSet(IndexMap<Type, Box<dyn Any>>);
+pub struct SingletonSet(IndexMap<Type, Slot>, Extras);
+
+/// Registrations that belong to a [`SingletonSet`] rather than to any
+/// value in it, so they outlive the values of their types.
+#[derive(Default)]
+struct Extras {
+    formatters: IndexMap<Type, name::FormatFn>,
+    measures: IndexMap<Type, heap_size::MeasureFn>,
+    namespaces: IndexMap<Type, SingletonSet>,
+    computed: IndexMap<Type, computed::Computed>,
+    sealed: IndexMap<Type, u64>,
+    validators: validate::Validators,
+    #[cfg(feature = "stats")]
+    stats: stats::Stats,
+}
+
+impl Extras {
+    /// Returns an error if the slot of `t` is sealed.
+    fn check_unsealed(&self, t: &Type) -> Result<(), Sealed> {
+        match self.sealed.contains_key(t) {
+            true => Err(Sealed(*t)),
+            false => Ok(()),
+        }
+    }
+
+    /// Panics if the slot of `t` is sealed, after emitting a `tracing`
+    /// event if the `tracing` feature is enabled.
+    fn assert_unsealed(&self, t: &Type) {
+        if let Err(err) = self.check_unsealed(t) {
+            trace_slot!(error, "change to a sealed slot", t);
+            panic!("{}", err)
+        }
+    }
+
+    /// Panics if the slot of `t` is sealed, and returns false if a value
+    /// being inserted into it is rejected by its validator.
+    fn admit(&mut self, t: &Type, value: &dyn Any) -> bool {
+        self.try_admit(t, value).is_ok()
+    }
+
+    /// Like [`.admit()`], but returns the failure of a rejected value.
+    ///
+    /// [`.admit()`]: Self::admit()
+    fn try_admit(&mut self, t: &Type, value: &dyn Any) -> Result<(), ValidationFailure> {
+        self.assert_unsealed(t);
+        self.validators.validate(t, value)
+    }
+
+    /// Panics if the slot of `t` is sealed, or if a value being inserted
+    /// into it that can't be handed back is rejected by its validator.
+    fn admit_or_panic(&mut self, t: &Type, value: &dyn Any) {
+        self.assert_unsealed(t);
+        self.validators.check_or_panic(t, value);
+    }
+
+    /// Forgets the memoized value of every computed value that depends on
+    /// the slot of `t`, directly or through other computed values.
+    fn changed(&mut self, t: &Type) {
+        if self.computed.is_empty() {
+            return;
+        }
+
+        let mut changed = vec![*t];
+        while let Some(t) = changed.pop() {
+            for (out, computed) in &mut self.computed {
+                if computed.depends_on(&t) && computed.invalidate() {
+                    changed.push(*out);
+                }
+            }
+        }
+    }
+
+    /// Forgets the memoized value of every computed value.
+    fn changed_all(&mut self) {
+        for computed in self.computed.values_mut() {
+            computed.invalidate();
+        }
+    }
+
+    /// Records that the value in the slot of `t` was borrowed immutably.
+    #[inline]
+    fn read(&self, t: &Type) {
+        #[cfg(feature = "stats")]
+        self.stats.read(t);
+        #[cfg(not(feature = "stats"))]
+        let _ = t;
+    }
+
+    /// Records that the value in the slot of `t` was borrowed mutably.
+    fn written(&mut self, t: &Type) {
+        self.assert_unsealed(t);
+        self.changed(t);
+        #[cfg(feature = "stats")]
+        self.stats.written(t);
+    }
+
+    /// Records that every value in the set was borrowed mutably.
+    fn written_all(&mut self) {
+        self.changed_all();
+        #[cfg(feature = "stats")]
+        self.stats.written_all();
+    }
+
+    /// Records that a value was inserted into the slot of `t`, which may
+    /// already have held one.
+    fn inserted(&mut self, t: &Type) {
+        self.assert_unsealed(t);
+        trace_slot!(debug, "insert", t);
+        self.changed(t);
+        #[cfg(feature = "stats")]
+        self.stats.inserted(t);
+    }
+
+    /// Records that the empty slot of `t` was filled on first use, by a
+    /// method like [`SingletonSet::as_mut_or_insert_with()`].
+    fn initialized(&mut self, t: &Type) {
+        self.assert_unsealed(t);
+        trace_slot!(debug, "initialize", t);
+        self.changed(t);
+        #[cfg(feature = "stats")]
+        self.stats.inserted(t);
+    }
+
+    /// Records that the slot of `t` was emptied.
+    fn removed(&mut self, t: &Type) {
+        self.assert_unsealed(t);
+        trace_slot!(debug, "remove", t);
+        self.changed(t);
+        #[cfg(feature = "stats")]
+        self.stats.removed(t);
+    }
+
+    /// Records that every slot in the set was emptied.
+    fn removed_all(&mut self) {
+        if let Some(t) = self.sealed.keys().next() {
+            self.assert_unsealed(t);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(target: "singletonset", "clear");
+        self.changed_all();
+        #[cfg(feature = "stats")]
+        self.stats.removed_all();
+    }
+}
+
+impl std::fmt::Debug for Extras {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut f = f.debug_struct("Extras");
+        f.field("formatters", &self.formatters.keys())
+            .field("measures", &self.measures.keys())
+            .field("namespaces", &self.namespaces)
+            .field("computed", &self.computed)
+            .field("sealed", &self.sealed.keys())
+            .field("validators", &self.validators);
+        #[cfg(feature = "stats")]
+        f.field("stats", &self.stats);
+        f.finish()
+    }
+}
 
 impl SingletonSet {
     /// Creates an empty `SingletonSet`.
@@ -33,7 +299,7 @@ impl SingletonSet {
     #[inline]
     #[must_use]
     pub fn new() -> Self {
-        SingletonSet(IndexMap::new())
+        SingletonSet(IndexMap::new(), Extras::default())
     }
 
     /// Creates an empty `SingletonSet` with at least the specified capacity.
@@ -51,7 +317,7 @@ impl SingletonSet {
     #[inline]
     #[must_use]
     pub fn with_capacity(capacity: usize) -> Self {
-        SingletonSet(IndexMap::with_capacity(capacity))
+        SingletonSet(IndexMap::with_capacity(capacity), Extras::default())
     }
 
     /// Returns the number of elements the set can hold without reallocating.
@@ -61,11 +327,28 @@ impl SingletonSet {
     }
 
     /// Returns the number of elements the set currently holds.
+    ///
+    /// Elements held by the set's namespaces are not counted. To count
+    /// them too, use [`.len_recursive()`].
+    ///
+    /// [`.len_recursive()`]: Self::len_recursive()
     #[inline]
     pub fn len(&self) -> usize {
         self.0.len()
     }
 
+    /// Returns the number of elements the set holds, including those held
+    /// by its namespaces and theirs.
+    pub fn len_recursive(&self) -> usize {
+        self.0.len()
+            + self
+                .1
+                .namespaces
+                .values()
+                .map(SingletonSet::len_recursive)
+                .sum::<usize>()
+    }
+
     /// Returns true if the set contains no elements.
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -73,8 +356,18 @@ impl SingletonSet {
     }
 
     /// Clears the set, removing all values.
+    ///
+    /// The set's namespaces are kept, along with their values.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if any slot is sealed. If this is not acceptable,
+    /// use [`.checked_clear()`].
+    ///
+    /// [`.checked_clear()`]: Self::checked_clear()
     #[inline]
     pub fn clear(&mut self) {
+        self.1.removed_all();
         self.0.clear()
     }
 
@@ -104,13 +397,62 @@ impl SingletonSet {
     }
 
     /// Inserts a value into the inferred type's slot.
+    ///
+    /// If the value is rejected by the type's validator, it is dropped, the
+    /// slot keeps its value, and [`None`] is returned, just as if the slot
+    /// had been empty. Apart from the entry in the [`ValidationReport`],
+    /// nothing signals the rejection; [`.validated_insert()`] returns it.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if the slot is sealed. If this is not acceptable,
+    /// use [`.checked_insert()`].
+    ///
+    /// [`.validated_insert()`]: Self::validated_insert()
+    /// [`.checked_insert()`]: Self::checked_insert()
     pub fn insert<T>(&mut self, value: T) -> Option<T>
     where
         T: 'static,
     {
-        self.0
-            .insert(Type::of::<T>(), Box::new(value))
-            .and_then(|boxed| boxed.downcast().ok().map(|boxed| *boxed))
+        self.validated_insert(value).ok().flatten()
+    }
+
+    /// Inserts a value into the inferred type's slot, unless it is rejected
+    /// by the type's validator.
+    ///
+    /// If the slot already held a value, that value is returned. If the
+    /// value is rejected, it is dropped, the slot keeps its value, and the
+    /// failure is returned as well as recorded in the
+    /// [`ValidationReport`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SingletonSet;
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.register_validator(|port: &u16| match *port {
+    ///     0 => Err("port 0 is reserved"),
+    ///     _ => Ok(()),
+    /// });
+    ///
+    /// assert_eq!(set.validated_insert(8080u16).unwrap(), None);
+    /// let err = set.validated_insert(0u16).unwrap_err();
+    /// assert_eq!(err.error().to_string(), "port 0 is reserved");
+    /// assert_eq!(set.get::<u16>(), &8080);
+    /// ```
+    pub fn validated_insert<T>(&mut self, value: T) -> Result<Option<T>, ValidationFailure>
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        self.1.try_admit(&t, &value)?;
+
+        self.1.inserted(&t);
+        Ok(self
+            .0
+            .insert(t, Slot::new(value))
+            .and_then(Slot::into_inner))
     }
 
     /// Inserts the default value of a type in the set.
@@ -129,317 +471,1779 @@ impl SingletonSet {
         self.insert(f())
     }
 
-    /// Returns true if the type is represented in the set.
-    pub fn contains<T>(&self) -> bool
+    /// Inserts each value of a [`Bundle`] into its type's slot.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SingletonSet;
+    /// let mut set = SingletonSet::new();
+    /// set.insert_bundle((1u8, 2u16, "three"));
+    /// assert_eq!(set.len(), 3);
+    /// ```
+    pub fn insert_bundle<B>(&mut self, bundle: B)
     where
-        T: 'static,
+        B: Bundle,
     {
-        self.0.contains_key(&Type::of::<T>())
+        bundle.insert_into(self)
     }
 
-    /// Returns true if the type of the provided value is represented in the
-    /// set.
-    pub fn contains_type_of<T>(&self, value: &T) -> bool
+    /// Removes the value from the inferred type's slot and returns it, if
+    /// it exists.
+    ///
+    /// If the slot is pinned, or is shared and other `Arc`s still point to
+    /// its value, the value can't be returned, so the slot is left in place
+    /// and [`None`] is returned. The same goes for a slot filled by
+    /// [`.insert_unsized()`], whose value can only be removed with
+    /// [`.remove_unsized()`].
+    ///
+    /// The order of the remaining types is preserved.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if the slot is sealed. If this is not acceptable,
+    /// use [`.checked_remove()`].
+    ///
+    /// [`.insert_unsized()`]: Self::insert_unsized()
+    /// [`.remove_unsized()`]: Self::remove_unsized()
+    /// [`.checked_remove()`]: Self::checked_remove()
+    pub fn remove<T>(&mut self) -> Option<T>
     where
         T: 'static,
     {
-        let _ = value;
-        self.0.contains_key(&Type::of::<T>())
+        let t = Type::of::<T>();
+        if !self.0.get(&t).map_or(false, Slot::can_take::<T>) {
+            return None;
+        }
+
+        self.1.removed(&t);
+        self.0.shift_remove(&t).and_then(Slot::into_inner)
     }
 
-    /// Returns true if the supplied [`Type`] is represented in the set.
-    pub fn contains_type(&self, t: &Type) -> bool {
-        self.0.contains_key(t)
+    /// Inserts a boxed value of an unsized type, such as `str`, `[T]` or
+    /// `dyn Trait`, into the slot of that type.
+    ///
+    /// The slot is keyed by [`Type::of_unsized::<U>()`], not by the type of
+    /// the box, and its value can be borrowed with [`.get_unsized()`]. If
+    /// the slot already held a value, that value is returned.
+    ///
+    /// `U` is meant to be unsized. A sized type's slot can be filled by
+    /// either this method or [`.insert()`], but each can only read back
+    /// values it inserted itself. Like [`.insert()`], this method drops a
+    /// value rejected by the type's validator and returns [`None`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::fmt::Display;
+    /// use singletonset::{SingletonSet, Type};
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.insert_unsized::<dyn Display>(Box::new(42));
+    /// set.insert_unsized::<str>("Hello".into());
+    ///
+    /// assert!(set.contains_type(&Type::of_unsized::<dyn Display>()));
+    /// assert_eq!(set.get_unsized::<dyn Display>().unwrap().to_string(), "42");
+    /// assert_eq!(set.get_unsized::<str>(), Some("Hello"));
+    /// ```
+    ///
+    /// [`Type::of_unsized::<U>()`]: Type::of_unsized()
+    /// [`.get_unsized()`]: Self::get_unsized()
+    /// [`.insert()`]: Self::insert()
+    pub fn insert_unsized<U>(&mut self, value: Box<U>) -> Option<Box<U>>
+    where
+        U: ?Sized + 'static,
+    {
+        let t = Type::of_unsized::<U>();
+        if !self.1.admit(&t, &value) {
+            return None;
+        }
+
+        self.1.inserted(&t);
+        self.0
+            .insert(t, Slot::from_parts(Box::new(value), Storage::Unsized))
+            .and_then(Slot::into_inner)
     }
 
-    /// Calls a closure with some value of the corresponding type's
-    /// slot, returning the closure's return value.
+    /// Inserts a value into the inferred type's slot, and remembers how to
+    /// measure the heap memory it owns for [`.memory_usage()`].
     ///
-    /// If the slot is empty, the closure is passed [`None`]. This method
-    /// will not initialize an empty slot.
-    pub fn try_with_ref<T, R>(&self, f: impl FnOnce(Option<&T>) -> R) -> R
+    /// Like the formatters of [`.register_debug()`], the measurement is
+    /// registered for the type rather than for the value, so later values
+    /// of the type are measured too, however they are inserted.
+    ///
+    /// [`.memory_usage()`]: Self::memory_usage()
+    /// [`.register_debug()`]: Self::register_debug()
+    pub fn insert_measured<T>(&mut self, value: T) -> Option<T>
     where
-        T: 'static,
+        T: HeapSize + 'static,
     {
-        f(self.try_as_ref())
+        self.1
+            .measures
+            .insert(Type::of::<T>(), heap_size::measure::<T>);
+        self.insert(value)
     }
 
-    /// Calls a provided closure with the value of the corresponding type's
-    /// slot, if it exists, returning its return value.
+    /// Inserts a value into the inferred type's slot, behind an [`Arc`] so
+    /// it can be shared with code that outlives a borrow of the set.
     ///
-    /// # Safety
+    /// The value can still be read with methods like [`.get()`], and a new
+    /// `Arc` pointing to it can be made with [`.get_arc()`]. Methods that
+    /// borrow the value mutably only succeed while the set holds the only
+    /// `Arc`; otherwise the `try_` methods return [`None`], and the others
+    /// panic.
     ///
-    /// This method panics if there is no existing value for the given type.
-    /// If this is not acceptable, use [`.try_with_ref()`],
-    /// [`.try_as_ref()`], or one of the `get_or` methods.
+    /// If the slot already held a value, that value is returned, wrapped in
+    /// a new `Arc` if it wasn't shared.
     ///
-    /// [`.try_with_ref()`]: Self::try_with_ref()
-    /// [`.try_as_ref()`]: Self::try_as_ref()
-    pub fn with_ref<T, R>(&self, f: impl FnOnce(&T) -> R) -> R
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::{SingletonSet, SlotKind, Type};
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.insert_shared("Hello".to_string());
+    ///
+    /// let shared = set.get_arc::<String>().unwrap();
+    /// let handle = std::thread::spawn(move || shared.len());
+    /// assert_eq!(handle.join().unwrap(), 5);
+    ///
+    /// assert_eq!(set.get::<String>(), "Hello");
+    /// assert_eq!(set.slot_kind(&Type::of::<String>()), Some(SlotKind::Shared));
+    /// ```
+    ///
+    /// [`.get()`]: Self::get()
+    /// [`.get_arc()`]: Self::get_arc()
+    pub fn insert_shared<T>(&mut self, value: T) -> Option<Arc<T>>
     where
         T: 'static,
     {
-        f(self.as_ref())
+        self.insert_arc(Arc::new(value))
     }
 
-    /// Inserts `default` if its type is not already represented, then calls
-    /// `f` with a reference to the value from the set.
+    /// Inserts an existing [`Arc`] into the slot of the type it points to.
     ///
-    /// This method also returns the closure's return value.
-    pub fn with_ref_or<T, R>(&mut self, default: T, f: impl FnOnce(&T) -> R) -> R
+    /// This works like [`.insert_shared()`], but the set shares the value
+    /// with the `Arc`s that already point to it.
+    ///
+    /// [`.insert_shared()`]: Self::insert_shared()
+    pub fn insert_arc<T>(&mut self, value: Arc<T>) -> Option<Arc<T>>
     where
         T: 'static,
     {
-        f(self.as_ref_or_insert::<T>(default))
+        if !self.1.admit(&Type::of::<T>(), &*value) {
+            return None;
+        }
+
+        self.1.inserted(&Type::of::<T>());
+        self.0
+            .insert(
+                Type::of::<T>(),
+                Slot::from_parts(Box::new(value), Storage::shared::<T>()),
+            )
+            .and_then(Slot::into_arc)
     }
 
-    /// Inserts the default value if its type is not already represented,
-    /// then calls `f` with a reference to the value from the set.
+    /// Returns a new [`Arc`] pointing to the value of the specified type,
+    /// if its slot is shared.
     ///
-    /// This method also returns the closure's return value.
-    pub fn with_ref_or_default<T, R>(&mut self, f: impl FnOnce(&T) -> R) -> R
+    /// Values inserted by methods like [`.insert()`] are owned by the set,
+    /// so this returns [`None`] for them.
+    ///
+    /// [`.insert()`]: Self::insert()
+    pub fn get_arc<T>(&self) -> Option<Arc<T>>
     where
-        T: 'static + Default,
+        T: 'static,
     {
-        f(self.as_ref_or_insert::<T>(T::default()))
+        let t = Type::of::<T>();
+        self.1.read(&t);
+        self.0
+            .get(&t)
+            .and_then(|slot| slot.raw().downcast_ref::<Arc<T>>())
+            .cloned()
     }
 
-    /// Applies a function to the immutable reference of a type in the set,
-    /// initializing the value first with a default function result (if the
-    /// type is not in the set), and returning the closure's return value.
-    pub fn with_ref_or_else<T, R>(
-        &mut self,
-        default: impl FnOnce() -> T,
-        f: impl FnOnce(&T) -> R,
-    ) -> R
+    /// Returns a [`Weak`] pointer to the value of the specified type, if its
+    /// slot is shared.
+    ///
+    /// Unlike an `Arc` from [`.get_arc()`], the `Weak` pointer doesn't keep
+    /// the value alive, so its holder can tell when the value has been
+    /// removed from or replaced in the set.
+    ///
+    /// [`.get_arc()`]: Self::get_arc()
+    pub fn downgrade<T>(&self) -> Option<Weak<T>>
     where
         T: 'static,
     {
-        f(self.as_ref_or_insert_with(default))
+        let t = Type::of::<T>();
+        self.1.read(&t);
+        self.0
+            .get(&t)
+            .and_then(|slot| slot.raw().downcast_ref::<Arc<T>>())
+            .map(Arc::downgrade)
     }
 
-    /// Calls a provided closure with some mutable reference to the
-    /// corresponding type's slot, returning the closure's return value.
+    /// Inserts a value into the inferred type's slot and pins it there.
     ///
-    /// If the slot is empty, the closure is passed [`None`].
-    pub fn try_with_mut<T, R>(&mut self, f: impl FnOnce(Option<&mut T>) -> R) -> R
+    /// The set promises not to move a pinned value until it is removed or
+    /// the set is dropped, so its address can be handed to code that keeps
+    /// it, such as a C library or an intrusive list. The value can be read
+    /// with methods like [`.get()`], and borrowed mutably through a [`Pin`]
+    /// with [`.get_pinned_mut()`].
+    ///
+    /// Since a `&mut` reference would allow the value to be moved, methods
+    /// that borrow it mutably treat the slot as unavailable: the `try_`
+    /// methods return [`None`], and the others panic. [`.insert()`] drops
+    /// the pinned value in place instead of returning it, and
+    /// [`.remove()`] leaves the slot alone and returns [`None`].
+    /// [`.remove_pinned()`] removes the value without moving it.
+    ///
+    /// Any value the slot held before is dropped.
+    ///
+    /// # Safety
+    ///
+    /// Since a pinned value can't be handed back, this method panics if the
+    /// value is rejected by the type's validator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::marker::PhantomPinned;
+    /// use singletonset::SingletonSet;
+    ///
+    /// struct Registered {
+    ///     id: u32,
+    ///     _pin: PhantomPinned,
+    /// }
+    ///
+    /// let mut set = SingletonSet::new();
+    /// let pinned = set.insert_pinned(Registered { id: 7, _pin: PhantomPinned });
+    /// let address = &*pinned as *const Registered;
+    ///
+    /// set.insert(1u8);
+    /// set.insert("grow the set".to_string());
+    ///
+    /// let pinned = set.get_pinned_mut::<Registered>().unwrap();
+    /// assert_eq!(&*pinned as *const Registered, address);
+    /// assert_eq!(set.get::<Registered>().id, 7);
+    /// ```
+    ///
+    /// [`.get()`]: Self::get()
+    /// [`.get_pinned_mut()`]: Self::get_pinned_mut()
+    /// [`.insert()`]: Self::insert()
+    /// [`.remove()`]: Self::remove()
+    /// [`.remove_pinned()`]: Self::remove_pinned()
+    pub fn insert_pinned<T>(&mut self, value: T) -> Pin<&mut T>
     where
         T: 'static,
     {
-        f(self.try_as_mut::<T>())
+        let t = Type::of::<T>();
+        self.1.admit_or_panic(&t, &value);
+        self.1.inserted(&t);
+        self.0.insert(
+            t,
+            Slot::from_parts(Box::new(Box::pin(value)), Storage::pinned::<T>()),
+        );
+
+        self.0
+            .get_mut(&t)
+            .and_then(|slot| slot.raw_mut().downcast_mut::<Pin<Box<T>>>())
+            .map(Pin::as_mut)
+            // Safety: The slot was just filled with a pinned value of this
+            // type, so this `expect()` will never panic.
+            .expect("a pinned value was just inserted")
     }
 
-    /// Calls a provided closure with the value of the corresponding type's
-    /// slot, if it exists, returning its return value.
-    pub fn with_mut<T, R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R
+    /// Returns a pinned mutable reference to the value of the specified
+    /// type, if its slot was filled by [`.insert_pinned()`].
+    ///
+    /// [`.insert_pinned()`]: Self::insert_pinned()
+    pub fn get_pinned_mut<T>(&mut self) -> Option<Pin<&mut T>>
     where
-        T: 'static + Default,
+        T: 'static,
     {
-        f(self.as_mut())
+        self.1.check_unsealed(&Type::of::<T>()).ok()?;
+        self.1.written(&Type::of::<T>());
+        self.0
+            .get_mut(&Type::of::<T>())
+            .and_then(|slot| slot.raw_mut().downcast_mut::<Pin<Box<T>>>())
+            .map(Pin::as_mut)
     }
 
-    /// Inserts `default` if its type is not already represented, then calls
-    /// `f` with a mutable reference to the value from the set.
+    /// Removes the pinned value of the specified type from the set without
+    /// moving it, if its slot was filled by [`.insert_pinned()`].
     ///
-    /// This method returns the closure's return value.
-    pub fn with_mut_or<T, R>(&mut self, default: T, f: impl FnOnce(&mut T) -> R) -> R
+    /// If the slot holds a value that isn't pinned, it is left in the set.
+    ///
+    /// [`.insert_pinned()`]: Self::insert_pinned()
+    pub fn remove_pinned<T>(&mut self) -> Option<Pin<Box<T>>>
     where
         T: 'static,
     {
-        f(self.as_mut_or_insert::<T>(default))
+        let t = Type::of::<T>();
+        if self.slot_kind(&t) != Some(SlotKind::Pinned) {
+            return None;
+        }
+
+        self.1.removed(&t);
+        self.0
+            .shift_remove(&t)
+            .and_then(|slot| slot.into_parts().0.downcast().ok())
+            .map(|pinned| *pinned)
     }
 
-    /// Applies a function to the mutable reference of a type in the set,
-    /// initializing the value first with a default function result (if the
-    /// type is not in the set), and returning the closure's return value.
-    pub fn with_mut_or_else<T, R>(
+    /// Inserts a boxed value into the slot of the given [`Type`], and
+    /// returns the value the slot held before, if any.
+    ///
+    /// This is meant for code that handles values without knowing their
+    /// types at compile time. If the value is not of type `t`, it is not
+    /// inserted, and is returned in the error instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::any::Any;
+    /// use singletonset::{SingletonSet, Type};
+    ///
+    /// let mut set = SingletonSet::new();
+    ///
+    /// let value: Box<dyn Any> = Box::new(42u8);
+    /// set.insert_boxed(Type::of::<u8>(), value).unwrap();
+    /// assert_eq!(set.get::<u8>(), &42);
+    ///
+    /// let err = set.insert_boxed(Type::of::<u16>(), Box::new(7u8)).unwrap_err();
+    /// assert_eq!(err.into_value().downcast_ref::<u8>(), Some(&7));
+    /// ```
+    pub fn insert_boxed(
         &mut self,
-        default: impl FnOnce() -> T,
-        f: impl FnOnce(&mut T) -> R,
-    ) -> R
+        t: Type,
+        value: Box<dyn Any>,
+    ) -> Result<Option<Box<dyn Any>>, TypeMismatch> {
+        if (*value).type_id() != t.to_id() {
+            return Err(TypeMismatch { expected: t, value });
+        }
+
+        if !self.1.admit(&t, &*value) {
+            return Ok(None);
+        }
+
+        self.1.inserted(&t);
+        Ok(self
+            .0
+            .insert(t, Slot::from_parts(value, Storage::Owned))
+            .map(|slot| slot.into_parts().0))
+    }
+
+    /// Removes the boxed value of an unsized type from the set and returns
+    /// it, if it exists.
+    ///
+    /// Only a value inserted with [`.insert_unsized()`] can be removed this
+    /// way. The slot of a sized type that was filled by another method is
+    /// left in place, and [`None`] is returned.
+    ///
+    /// [`.insert_unsized()`]: Self::insert_unsized()
+    pub fn remove_unsized<U>(&mut self) -> Option<Box<U>>
     where
-        T: 'static,
+        U: ?Sized + 'static,
     {
-        f(self.as_mut_or_insert_with(default))
+        let t = Type::of_unsized::<U>();
+        if !self.0.get(&t).map_or(false, Slot::can_take::<Box<U>>) {
+            return None;
+        }
+
+        self.1.removed(&t);
+        self.0.shift_remove(&t).and_then(Slot::into_inner)
     }
 
-    /// This is an alias for [`Self::as_ref()`]
-    pub fn get<T>(&self) -> &T
+    /// Removes the value of every type in a [`Bundle`] and returns them as a
+    /// tuple.
+    ///
+    /// If any of the types is missing, nothing is removed and the error
+    /// lists every missing [`Type`].
+    pub fn take_bundle<B>(&mut self) -> Result<B, MissingTypes>
     where
-        T: 'static,
+        B: Bundle,
     {
-        self.as_ref()
+        B::take_from(self)
     }
 
-    /// Returns an immutable reference to the value of the specified type,
+    /// Builds a [`FromSingletonSet`] value out of the contents of the set.
+    ///
+    /// If any required field is missing, or any field would take a value
+    /// the set doesn't own outright, the set is left untouched and the
+    /// error names every such field and its [`Type`].
+    pub fn extract<S>(&mut self) -> Result<S, MissingFields>
+    where
+        S: FromSingletonSet,
+    {
+        S::from_singleton_set(self)
+    }
+
+    /// Inserts each field of an [`IntoSingletonSet`] value into the slot of
+    /// its type.
+    pub fn insert_fields<S>(&mut self, value: S)
+    where
+        S: IntoSingletonSet,
+    {
+        value.insert_into_set(self)
+    }
+
+    /// Inserts a default value of every type in the [`registry`] that was
+    /// registered with a default, unless the set already holds a value of
+    /// that type.
+    ///
+    /// A default that is rejected by its type's validator is not inserted.
+    ///
+    /// This method is only available with the `inventory` feature.
+    #[cfg(feature = "inventory")]
+    pub fn populate_defaults(&mut self) {
+        for registered in registry::all() {
+            let t = registered.to_type();
+            if self.contains_type(&t) {
+                continue;
+            }
+
+            if let Some(value) = registered.default_value() {
+                if !self.1.admit(&t, &*value) {
+                    continue;
+                }
+
+                self.1.initialized(&t);
+                self.0.insert(t, Slot::from_parts(value, Storage::Owned));
+            }
+        }
+    }
+
+    /// Returns true if the type is represented in the set.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.0.contains_key(&Type::of::<T>())
+    }
+
+    /// Returns true if the type of the provided value is represented in the
+    /// set.
+    pub fn contains_type_of<T>(&self, value: &T) -> bool
+    where
+        T: 'static,
+    {
+        let _ = value;
+        self.0.contains_key(&Type::of::<T>())
+    }
+
+    /// Returns true if the supplied [`Type`] is represented in the set.
+    pub fn contains_type(&self, t: &Type) -> bool {
+        self.0.contains_key(t)
+    }
+
+    /// Returns the [`Type`] in the set that registered the given
+    /// [`StableKey`], if there is one.
+    ///
+    /// Only types that have been registered with
+    /// [`StableTypeKey::register()`] can be found this way.
+    pub fn type_by_stable_key(&self, key: &StableKey) -> Option<Type> {
+        key.registered_type().filter(|t| self.contains_type(t))
+    }
+
+    /// Returns true if every type in the [`Bundle`] is represented in the
+    /// set.
+    pub fn contains_all<B>(&self) -> bool
+    where
+        B: Bundle,
+    {
+        B::types().iter().all(|t| self.contains_type(t))
+    }
+
+    /// Returns the types in `types` that are not represented in the set.
+    pub(crate) fn missing_types(&self, types: &TypeSet) -> TypeSet {
+        types.iter().filter(|t| !self.contains_type(t)).collect()
+    }
+
+    /// Returns true if the slot of `T` holds a value that [`.remove()`]
+    /// can't return.
+    ///
+    /// [`.remove()`]: Self::remove()
+    pub(crate) fn is_not_owned<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.0
+            .get(&Type::of::<T>())
+            .map_or(false, |slot| !slot.can_take::<T>())
+    }
+
+    /// Calls a closure with some value of the corresponding type's
+    /// slot, returning the closure's return value.
+    ///
+    /// If the slot is empty, the closure is passed [`None`]. This method
+    /// will not initialize an empty slot.
+    pub fn try_with_ref<T, R>(&self, f: impl FnOnce(Option<&T>) -> R) -> R
+    where
+        T: 'static,
+    {
+        f(self.try_as_ref())
+    }
+
+    /// Calls a provided closure with the value of the corresponding type's
+    /// slot, if it exists, returning its return value.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if there is no existing value for the given type.
+    /// If this is not acceptable, use [`.try_with_ref()`],
+    /// [`.try_as_ref()`], or one of the `get_or` methods.
+    ///
+    /// [`.try_with_ref()`]: Self::try_with_ref()
+    /// [`.try_as_ref()`]: Self::try_as_ref()
+    pub fn with_ref<T, R>(&self, f: impl FnOnce(&T) -> R) -> R
+    where
+        T: 'static,
+    {
+        f(self.as_ref())
+    }
+
+    /// Inserts `default` if its type is not already represented, then calls
+    /// `f` with a reference to the value from the set.
+    ///
+    /// This method also returns the closure's return value.
+    pub fn with_ref_or<T, R>(&mut self, default: T, f: impl FnOnce(&T) -> R) -> R
+    where
+        T: 'static,
+    {
+        f(self.as_ref_or_insert::<T>(default))
+    }
+
+    /// Inserts the default value if its type is not already represented,
+    /// then calls `f` with a reference to the value from the set.
+    ///
+    /// This method also returns the closure's return value.
+    pub fn with_ref_or_default<T, R>(&mut self, f: impl FnOnce(&T) -> R) -> R
+    where
+        T: 'static + Default,
+    {
+        f(self.as_ref_or_insert::<T>(T::default()))
+    }
+
+    /// Applies a function to the immutable reference of a type in the set,
+    /// initializing the value first with a default function result (if the
+    /// type is not in the set), and returning the closure's return value.
+    pub fn with_ref_or_else<T, R>(
+        &mut self,
+        default: impl FnOnce() -> T,
+        f: impl FnOnce(&T) -> R,
+    ) -> R
+    where
+        T: 'static,
+    {
+        f(self.as_ref_or_insert_with(default))
+    }
+
+    /// Calls a provided closure with some mutable reference to the
+    /// corresponding type's slot, returning the closure's return value.
+    ///
+    /// If the slot is empty, the closure is passed [`None`].
+    pub fn try_with_mut<T, R>(&mut self, f: impl FnOnce(Option<&mut T>) -> R) -> R
+    where
+        T: 'static,
+    {
+        f(self.try_as_mut::<T>())
+    }
+
+    /// Calls a provided closure with the value of the corresponding type's
+    /// slot, if it exists, returning its return value.
+    ///
+    /// The value is validated after the closure returns.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if the slot is sealed. If this is not acceptable,
+    /// use [`.checked_with_mut()`].
+    ///
+    /// [`.checked_with_mut()`]: Self::checked_with_mut()
+    pub fn with_mut<T, R>(&mut self, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: 'static + Default,
+    {
+        self.as_mut_or_insert_with(T::default);
+        self.with_validated_mut(f).0
+    }
+
+    /// Calls a closure with the value of the corresponding type's slot,
+    /// inserting the default value first if the type isn't in the set, and
+    /// returns the closure's return value unless the change is rejected by
+    /// the type's validator.
+    ///
+    /// If the change is rejected, the value is restored to what it was
+    /// before the closure was called, and the failure is returned.
+    pub fn validated_with_mut<T, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, ValidationFailure>
+    where
+        T: 'static + Default,
+    {
+        self.as_mut_or_insert_with(T::default);
+        let (result, validated) = self.with_validated_mut(f);
+        validated.map(|()| result)
+    }
+
+    /// Inserts `default` if its type is not already represented, then calls
+    /// `f` with a mutable reference to the value from the set.
+    ///
+    /// This method returns the closure's return value.
+    pub fn with_mut_or<T, R>(&mut self, default: T, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: 'static,
+    {
+        self.as_mut_or_insert::<T>(default);
+        self.with_validated_mut(f).0
+    }
+
+    /// Applies a function to the mutable reference of a type in the set,
+    /// initializing the value first with a default function result (if the
+    /// type is not in the set), and returning the closure's return value.
+    ///
+    /// The value is validated after the closure returns.
+    pub fn with_mut_or_else<T, R>(
+        &mut self,
+        default: impl FnOnce() -> T,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> R
+    where
+        T: 'static,
+    {
+        self.as_mut_or_insert_with(default);
+        self.with_validated_mut(f).0
+    }
+
+    /// Calls `f` with the value of the slot of `T`, which must not be empty,
+    /// then validates the value, restoring the value it had before if the
+    /// change is rejected.
+    fn with_validated_mut<T, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> (R, Result<(), ValidationFailure>)
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        let slot = self
+            .0
+            .get_mut(&t)
+            .unwrap_or_else(|| slot_panic(&t, MISSING));
+        let backup = self.1.validators.backup(&t, slot.get());
+        let value = slot
+            .downcast_mut::<T>()
+            .unwrap_or_else(|| slot_panic(&t, NOT_MUT));
+
+        let result = f(value);
+        let validated = self
+            .1
+            .validators
+            .validate_change(&t, value, backup.is_some());
+        if validated.is_err() {
+            if let Some(backup) = backup.and_then(|backup| backup.downcast().ok()) {
+                *value = *backup;
+            }
+        }
+
+        (result, validated)
+    }
+
+    /// This is an alias for [`Self::as_ref()`]
+    pub fn get<T>(&self) -> &T
+    where
+        T: 'static,
+    {
+        self.as_ref()
+    }
+
+    /// Returns an immutable reference to the value of the specified type,
     /// if it exists.
     ///
     /// This method does not insert an element into the set, so it can be
     /// used with types that do not implement [`Default`] and does not need
-    /// the set to be mutable.
+    /// the set to be mutable. If the slot is empty but the type was
+    /// registered with [`.register_computed()`], the computed value is
+    /// returned.
+    ///
+    /// [`.register_computed()`]: Self::register_computed()
     #[doc(alias = "try_get()")]
     pub fn try_as_ref<T>(&self) -> Option<&T>
     where
         T: 'static,
     {
-        self.0
-            .get(&Type::of::<T>())
-            .and_then(|boxed| boxed.downcast_ref::<T>())
+        let t = Type::of::<T>();
+        match self.0.get(&t) {
+            Some(slot) => {
+                self.1.read(&t);
+                slot.downcast_ref()
+            }
+            None => self.get_computed(&t)?.downcast_ref(),
+        }
+    }
+
+    /// This is an alias for [`Self::try_as_ref()`]
+    pub fn try_get<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        self.try_as_ref()
+    }
+
+    /// Returns an immutable reference to the value in the slot of the given
+    /// [`Type`], if it exists.
+    ///
+    /// The value of a slot filled by [`.insert_unsized()`] is the `Box<U>`
+    /// that was inserted. The value of a shared slot is the value its `Arc`
+    /// points to.
+    ///
+    /// [`.insert_unsized()`]: Self::insert_unsized()
+    pub fn get_by_type(&self, t: &Type) -> Option<&dyn Any> {
+        match self.0.get(t) {
+            Some(slot) => {
+                self.1.read(t);
+                Some(slot.get())
+            }
+            None => self.get_computed(t),
+        }
+    }
+
+    /// Returns a mutable reference to the value in the slot of the given
+    /// [`Type`], if it exists and can be borrowed mutably.
+    pub fn get_by_type_mut(&mut self, t: &Type) -> Option<&mut dyn Any> {
+        self.1.check_unsealed(t).ok()?;
+        let value = self.0.get_mut(t).and_then(Slot::get_mut)?;
+        self.1.written(t);
+        Some(value)
+    }
+
+    /// Returns the [`Type`] in the set with the given name.
+    ///
+    /// A name that matches the full name of a type, as returned by
+    /// [`Type::as_str()`], always wins. Otherwise, the name is compared with
+    /// the short name from [`Type::as_name()`], and with the short form of
+    /// the parsed name, which keeps generic arguments, as in `Vec<u8>`.
+    ///
+    /// If no type or more than one type has the name, the error says so and
+    /// lists every match. [`.find_all_by_name()`] returns the matches
+    /// without treating more than one as an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::{SingletonSet, Type};
+    ///
+    /// #[derive(Debug)]
+    /// struct Config(u8);
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.insert(Config(3));
+    /// set.register_debug::<Config>();
+    ///
+    /// let t = set.find_by_name("Config").unwrap();
+    /// assert_eq!(t, Type::of::<Config>());
+    /// assert_eq!(set.format_by_type(&t).unwrap().to_string(), "Config(3)");
+    /// ```
+    ///
+    /// [`.find_all_by_name()`]: Self::find_all_by_name()
+    pub fn find_by_name(&self, name: &str) -> Result<Type, FindByNameError> {
+        let mut found = self.find_all_by_name(name);
+
+        match found.len() {
+            0 => Err(FindByNameError::NotFound(name.to_string())),
+            1 => Ok(found.remove(0)),
+            _ => Err(FindByNameError::Ambiguous(name.to_string(), found)),
+        }
+    }
+
+    /// Returns every [`Type`] in the set with the given name, in the order
+    /// of the set.
+    ///
+    /// Names are matched as described for [`.find_by_name()`].
+    ///
+    /// [`.find_by_name()`]: Self::find_by_name()
+    pub fn find_all_by_name(&self, name: &str) -> Vec<Type> {
+        let full: Vec<_> = self
+            .types()
+            .filter(|t| t.as_str() == name)
+            .copied()
+            .collect();
+        if !full.is_empty() {
+            return full;
+        }
+
+        self.types()
+            .filter(|t| t.as_name() == name || t.parsed_name().short().to_string() == name)
+            .copied()
+            .collect()
+    }
+
+    /// Registers the [`Debug`] implementation of a type, so the value in
+    /// its slot can be formatted by [`.format_by_type()`].
+    ///
+    /// The registration belongs to the set, not to the value, so it is
+    /// kept when the value is replaced or removed. It replaces any earlier
+    /// registration for the same type.
+    ///
+    /// [`Debug`]: std::fmt::Debug
+    /// [`.format_by_type()`]: Self::format_by_type()
+    pub fn register_debug<T>(&mut self)
+    where
+        T: std::fmt::Debug + 'static,
+    {
+        self.1
+            .formatters
+            .insert(Type::of::<T>(), name::format_debug::<T>);
+    }
+
+    /// Registers the [`Display`] implementation of a type, so the value in
+    /// its slot can be formatted by [`.format_by_type()`].
+    ///
+    /// Like [`.register_debug()`], this replaces any earlier registration
+    /// for the same type.
+    ///
+    /// [`.format_by_type()`]: Self::format_by_type()
+    /// [`.register_debug()`]: Self::register_debug()
+    pub fn register_display<T>(&mut self)
+    where
+        T: Display + 'static,
+    {
+        self.1
+            .formatters
+            .insert(Type::of::<T>(), name::format_display::<T>);
+    }
+
+    /// Returns the value in the slot of the given [`Type`], ready to be
+    /// formatted, if the slot holds a value and a formatter was registered
+    /// for its type with [`.register_debug()`] or [`.register_display()`].
+    ///
+    /// [`.register_debug()`]: Self::register_debug()
+    /// [`.register_display()`]: Self::register_display()
+    pub fn format_by_type(&self, t: &Type) -> Option<FormattedSlot<'_>> {
+        let format = *self.1.formatters.get(t)?;
+        let value = self.get_by_type(t)?;
+
+        Some(FormattedSlot { value, format })
+    }
+
+    /// Returns an immutable reference to the value of an unsized type that
+    /// was inserted with [`.insert_unsized()`], if it exists.
+    ///
+    /// [`.insert_unsized()`]: Self::insert_unsized()
+    pub fn get_unsized<U>(&self) -> Option<&U>
+    where
+        U: ?Sized + 'static,
+    {
+        let t = Type::of_unsized::<U>();
+        self.1.read(&t);
+        self.0
+            .get(&t)
+            .and_then(|slot| slot.raw().downcast_ref::<Box<U>>())
+            .map(|boxed| &**boxed)
+    }
+
+    /// Returns a mutable reference to the value of an unsized type that was
+    /// inserted with [`.insert_unsized()`], if it exists.
+    ///
+    /// [`.insert_unsized()`]: Self::insert_unsized()
+    pub fn get_unsized_mut<U>(&mut self) -> Option<&mut U>
+    where
+        U: ?Sized + 'static,
+    {
+        self.1.check_unsealed(&Type::of_unsized::<U>()).ok()?;
+        self.1.written(&Type::of_unsized::<U>());
+        self.0
+            .get_mut(&Type::of_unsized::<U>())
+            .and_then(|slot| slot.raw_mut().downcast_mut::<Box<U>>())
+            .map(|boxed| &mut **boxed)
+    }
+
+    /// Returns an immutable reference to the value of every type in the
+    /// [`Bundle`], or [`None`] if any of them is missing.
+    pub fn try_get_all<'a, B>(&'a self) -> Option<B::Refs>
+    where
+        B: BundleRef<'a>,
+    {
+        B::get_from(self)
+    }
+
+    /// This is an alias for [`Self::as_mut()`]
+    pub fn get_mut<T>(&mut self) -> &mut T
+    where
+        T: 'static + Default,
+    {
+        self.as_mut()
+    }
+
+    /// Returns a mutable reference to the value of the specified type,
+    /// if it exists and its slot isn't sealed.
+    ///
+    /// This method does not insert an element into the set, so it can be
+    /// used with types that do not implement [`Default`].
+    #[doc(alias = "try_get_mut()")]
+    pub fn try_as_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        self.1.check_unsealed(&t).ok()?;
+        let value = self.0.get_mut(&t).and_then(Slot::downcast_mut)?;
+        self.1.written(&t);
+        Some(value)
+    }
+
+    /// This is an alias for [`Self::try_as_mut()`]
+    pub fn try_get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        self.try_as_mut()
+    }
+
+    /// Returns an immutable reference to the value of the specified type,
+    /// inserting the provided value if the type isn't already in the set.
+    ///
+    /// If the type is already represented in the set, the provided value
+    /// is ignored.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if the slot was filled by [`.insert_unsized()`],
+    /// whose value can only be read with [`.get_unsized()`].
+    ///
+    /// [`.insert_unsized()`]: Self::insert_unsized()
+    /// [`.get_unsized()`]: Self::get_unsized()
+    #[doc(alias = "get_or_insert()")]
+    pub fn as_ref_or_insert<T>(&mut self, value: T) -> &T
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if self.contains_type(&t) {
+            self.1.read(&t);
+        }
+
+        let extras = &mut self.1;
+        self.0
+            .entry(t)
+            .or_insert_with(|| {
+                extras.validators.check_or_panic(&t, &value);
+                extras.initialized(&t);
+                Slot::new(value)
+            })
+            .downcast_ref::<T>()
+            .unwrap_or_else(|| slot_panic(&t, UNSIZED))
+    }
+
+    /// This is an alias for [`Self::as_ref_or_insert()`]
+    pub fn get_or_insert<T>(&mut self, value: T) -> &T
+    where
+        T: 'static,
+    {
+        self.as_ref_or_insert(value)
+    }
+
+    /// Returns a mutable reference to the value of the specified type,
+    /// inserting the provided value if the type isn't already in the set.
+    ///
+    /// If the type is already represented in the set, the provided value
+    /// is ignored. To avoid allocating memory for a default value that is
+    /// discarded, use [`.get_or_insert_with_mut()`].
+    ///
+    /// [`.get_or_insert_with_mut()`]: Self::get_or_insert_with_mut()
+    #[doc(alias = "get_or_insert_mut()")]
+    pub fn as_mut_or_insert<T>(&mut self, value: T) -> &mut T
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if self.contains_type(&t) {
+            self.1.written(&t);
+        }
+
+        let extras = &mut self.1;
+        self.0
+            .entry(t)
+            .or_insert_with(|| {
+                extras.validators.check_or_panic(&t, &value);
+                extras.initialized(&t);
+                Slot::new(value)
+            })
+            .downcast_mut::<T>()
+            .unwrap_or_else(|| slot_panic(&t, NOT_MUT))
+    }
+
+    /// This is an alias for [`.as_mut_or_insert(value)`]
+    ///
+    /// [`.as_mut_or_insert(value)`]: Self::as_mut_or_insert()
+    pub fn get_or_insert_mut<T>(&mut self, value: T) -> &mut T
+    where
+        T: 'static,
+    {
+        self.as_mut_or_insert(value)
+    }
+
+    /// Returns an immutable reference to the value of the specified type,
+    /// inserting the return value of the provided method if the type isn't
+    /// already in the set.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if the slot was filled by [`.insert_unsized()`],
+    /// whose value can only be read with [`.get_unsized()`].
+    ///
+    /// [`.insert_unsized()`]: Self::insert_unsized()
+    /// [`.get_unsized()`]: Self::get_unsized()
+    #[doc(alias = "get_or_insert_mut()")]
+    pub fn as_ref_or_insert_with<T>(&mut self, default: impl FnOnce() -> T) -> &T
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if self.contains_type(&t) {
+            self.1.read(&t);
+        }
+
+        let extras = &mut self.1;
+        self.0
+            .entry(t)
+            .or_insert_with(traced(t, || {
+                let value = default();
+                extras.validators.check_or_panic(&t, &value);
+                extras.initialized(&t);
+                Slot::new(value)
+            }))
+            .downcast_ref::<T>()
+            .unwrap_or_else(|| slot_panic(&t, UNSIZED))
+    }
+
+    /// This is an alias for [`Self::as_ref_or_insert_with()`]
+    pub fn get_or_insert_with<T>(&mut self, default: impl FnOnce() -> T) -> &T
+    where
+        T: 'static,
+    {
+        self.as_ref_or_insert_with(default)
+    }
+
+    /// Returns a mutable reference to the value of the specified type,
+    /// inserting the return value of the provided method if the type isn't
+    /// already in the set.
+    #[doc(alias = "get_or_insert_with_mut()")]
+    pub fn as_mut_or_insert_with<T>(&mut self, default: impl FnOnce() -> T) -> &mut T
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if self.contains_type(&t) {
+            self.1.written(&t);
+        }
+
+        let extras = &mut self.1;
+        self.0
+            .entry(t)
+            .or_insert_with(traced(t, || {
+                let value = default();
+                extras.validators.check_or_panic(&t, &value);
+                extras.initialized(&t);
+                Slot::new(value)
+            }))
+            .downcast_mut::<T>()
+            .unwrap_or_else(|| slot_panic(&t, NOT_MUT))
+    }
+
+    /// This is an alias for [`.as_mut_or_insert_with(default)`]
+    ///
+    /// [`.as_mut_or_insert_with(default)`]: Self::as_mut_or_insert_with()
+    pub fn get_or_insert_with_mut<T>(&mut self, default: impl FnOnce() -> T) -> &mut T
+    where
+        T: 'static,
+    {
+        self.as_mut_or_insert_with(default)
+    }
+
+    /// Returns an iterator that visits each [`Type`] in the set in random order.
+    ///
+    /// The random order is inherited from the internal hash map used to
+    /// store the elements, but may change in the future.
+    pub fn types(&self) -> Types<'_> {
+        Types(TypesInner::Map(self.0.keys()))
+    }
+
+    /// Returns how the value in the slot of the given [`Type`] is stored, if
+    /// the slot holds a value.
+    pub fn slot_kind(&self, t: &Type) -> Option<SlotKind> {
+        self.0.get(t).map(Slot::kind)
+    }
+
+    /// Returns true if the slot of the specified type holds a shared value.
+    pub fn is_shared<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.slot_kind(&Type::of::<T>()) == Some(SlotKind::Shared)
+    }
+
+    /// Returns an iterator that visits each [`Type`] in the set along with
+    /// how its value is stored, in the same order as [`.types()`].
+    ///
+    /// [`.types()`]: Self::types()
+    pub fn slot_kinds(&self) -> impl Iterator<Item = (&Type, SlotKind)> {
+        self.0.iter().map(|(t, slot)| (t, slot.kind()))
+    }
+
+    /// Returns an iterator that visits the [`Type`] and value of each slot
+    /// in the set, in the same order as [`.types()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SingletonSet;
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.insert(1u8);
+    /// set.insert("two");
+    ///
+    /// for (t, value) in set.iter() {
+    ///     if let Some(n) = value.downcast_ref::<u8>() {
+    ///         println!("{} holds {}", t, n);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// [`.types()`]: Self::types()
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter())
+    }
+
+    /// Returns an iterator that visits the [`Type`] of each slot in the set
+    /// along with a mutable reference to its value, in the same order as
+    /// [`.types()`].
+    ///
+    /// [`.types()`]: Self::types()
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        self.1.written_all();
+        IterMut(self.0.iter_mut(), &self.1.sealed)
+    }
+
+    /// Returns an owned [`TypeSet`] holding each [`Type`] in the set, in the
+    /// same order as [`.types()`].
+    ///
+    /// [`.types()`]: Self::types()
+    pub fn type_set(&self) -> TypeSet {
+        self.types().collect()
+    }
+
+    /// Returns a [`LayoutReport`] describing the memory layout of the value
+    /// in each slot of the set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SingletonSet;
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.insert(1u32);
+    /// set.insert(2u64);
+    ///
+    /// let report = set.layout_report();
+    /// assert_eq!(report.total_size(), 12);
+    /// assert_eq!(report.max_align(), 8);
+    /// ```
+    pub fn layout_report(&self) -> LayoutReport {
+        LayoutReport::new(self.types().copied().collect())
+    }
+
+    /// Sets the [`Clock`] that the set's statistics are taken from.
+    ///
+    /// Only times recorded after the call are taken from the new clock.
+    /// Namespaces keep their own clocks.
+    ///
+    /// This method is only available with the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn set_clock(&mut self, clock: impl Clock) {
+        self.1.stats.set_clock(clock);
+    }
+
+    /// Returns the access statistics of every slot in the set, in the same
+    /// order as [`.types()`].
+    ///
+    /// Reads are counted by methods that borrow a value of a known type,
+    /// like [`.get()`] and [`.get_by_type()`], but not by iterators. Values
+    /// held by namespaces have their own statistics.
+    ///
+    /// This method is only available with the `stats` feature.
+    ///
+    /// [`.types()`]: Self::types()
+    /// [`.get()`]: Self::get()
+    /// [`.get_by_type()`]: Self::get_by_type()
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> StatsReport {
+        self.1.stats.report(self.0.keys())
+    }
+
+    /// Returns the access statistics of the slot of the specified type, if
+    /// it holds a value.
+    ///
+    /// This method is only available with the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn stats_for<T>(&self) -> Option<SlotStats>
+    where
+        T: 'static,
+    {
+        self.1.stats.get(&Type::of::<T>())
+    }
+
+    /// Registers a value of type `Out` that is computed from the values of
+    /// the types in the [`Bundle`] `B`.
+    ///
+    /// Reading `Out` with methods like [`.get()`] and [`.try_get()`] calls
+    /// `f` with a tuple of references to the inputs and keeps the result.
+    /// Later reads return the same value, until the slot of one of the
+    /// inputs is replaced, removed, or borrowed mutably, at which point the
+    /// value is computed again the next time it is read. If an input is
+    /// missing, `Out` reads as missing too.
+    ///
+    /// Computed values can be inputs of other computed values. A value in
+    /// the slot of `Out` itself takes precedence over the computed one, and
+    /// computed values aren't counted by methods like [`.contains()`] and
+    /// [`.len()`]. The registration replaces any earlier one for `Out`.
+    ///
+    /// # Safety
+    ///
+    /// Reading a computed value panics if it depends on itself, directly
+    /// or through other computed values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SingletonSet;
+    ///
+    /// struct Config {
+    ///     timeout: u32,
+    /// }
+    ///
+    /// struct Overrides {
+    ///     timeout: Option<u32>,
+    /// }
+    ///
+    /// struct Timeout(u32);
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.insert(Config { timeout: 30 });
+    /// set.insert(Overrides { timeout: None });
+    /// set.register_computed::<Timeout, (Config, Overrides)>(|(config, overrides)| {
+    ///     Timeout(overrides.timeout.unwrap_or(config.timeout))
+    /// });
+    ///
+    /// assert_eq!(set.get::<Timeout>().0, 30);
+    ///
+    /// set.try_get_mut::<Overrides>().unwrap().timeout = Some(5);
+    /// assert_eq!(set.get::<Timeout>().0, 5);
+    /// ```
+    ///
+    /// [`.get()`]: Self::get()
+    /// [`.try_get()`]: Self::try_get()
+    /// [`.contains()`]: Self::contains()
+    /// [`.len()`]: Self::len()
+    pub fn register_computed<Out, B>(
+        &mut self,
+        f: impl for<'a> Fn(<B as BundleRef<'a>>::Refs) -> Out + 'static,
+    ) where
+        Out: 'static,
+        B: for<'a> BundleRef<'a>,
+    {
+        let t = Type::of::<Out>();
+        self.1.changed(&t);
+        self.1
+            .computed
+            .insert(t, computed::Computed::new::<Out, B>(f));
+    }
+
+    /// Removes the registration of the computed value of type `Out`, and
+    /// returns true if there was one.
+    ///
+    /// See [`.register_computed()`] for details.
+    ///
+    /// [`.register_computed()`]: Self::register_computed()
+    pub fn unregister_computed<Out>(&mut self) -> bool
+    where
+        Out: 'static,
+    {
+        let t = Type::of::<Out>();
+        self.1.changed(&t);
+        self.1.computed.shift_remove(&t).is_some()
+    }
+
+    /// Returns the computed value of the given [`Type`], computing it first
+    /// if it isn't memoized.
+    fn get_computed(&self, t: &Type) -> Option<&dyn Any> {
+        self.1.computed.get(t)?.get(self)
+    }
+
+    /// Returns a [`MemoryUsage`] report of the memory used by each slot of
+    /// the set, including the set's own bookkeeping.
+    ///
+    /// The heap memory owned by a value is only known if its type was
+    /// inserted with [`.insert_measured()`]. Values held by the set's
+    /// namespaces are not included.
+    ///
+    /// [`.insert_measured()`]: Self::insert_measured()
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            slots: self
+                .0
+                .iter()
+                .map(|(t, slot)| SlotMemory {
+                    t: *t,
+                    heap: self.1.measures.get(t).map(|measure| measure(slot.get())),
+                    overhead: heap_size::INDEX_ENTRY_SIZE + heap_size::box_overhead(slot.kind()),
+                })
+                .collect(),
+            spare_capacity: (self.0.capacity() - self.0.len()) * heap_size::INDEX_ENTRY_SIZE,
+        }
+    }
+
+    /// Returns the namespace of the marker type `N`, if it exists.
+    ///
+    /// A namespace is a separate `SingletonSet` that lives inside this one.
+    /// Its slots don't collide with the slots of this set or of other
+    /// namespaces, so several libraries can each keep, for example, their
+    /// own `Config` in a set they share. `N` is usually a type that is
+    /// private to the library that owns the namespace.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SingletonSet;
+    ///
+    /// struct LibA;
+    /// struct LibB;
+    ///
+    /// #[derive(Default)]
+    /// struct Config(u8);
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.namespace_mut::<LibA>().insert(Config(1));
+    /// set.namespace_mut::<LibB>().insert(Config(2));
+    ///
+    /// assert!(!set.contains::<Config>());
+    /// assert_eq!(set.namespace::<LibA>().unwrap().get::<Config>().0, 1);
+    /// assert_eq!(set.namespace::<LibB>().unwrap().get::<Config>().0, 2);
+    ///
+    /// assert_eq!(set.len(), 0);
+    /// assert_eq!(set.len_recursive(), 2);
+    /// ```
+    pub fn namespace<N>(&self) -> Option<&SingletonSet>
+    where
+        N: 'static,
+    {
+        self.1.namespaces.get(&Type::of::<N>())
+    }
+
+    /// Returns a mutable reference to the namespace of the marker type `N`,
+    /// creating an empty one if it doesn't exist.
+    ///
+    /// See [`.namespace()`] for details.
+    ///
+    /// [`.namespace()`]: Self::namespace()
+    pub fn namespace_mut<N>(&mut self) -> &mut SingletonSet
+    where
+        N: 'static,
+    {
+        self.1.namespaces.entry(Type::of::<N>()).or_default()
+    }
+
+    /// Returns an iterator that visits the marker [`Type`] of each of the
+    /// set's namespaces, in the order they were created.
+    pub fn namespaces(&self) -> Types<'_> {
+        Types(TypesInner::Namespaces(self.1.namespaces.keys()))
+    }
+
+    /// Removes the namespace of the marker type `N` and returns it, if it
+    /// exists.
+    pub fn remove_namespace<N>(&mut self) -> Option<SingletonSet>
+    where
+        N: 'static,
+    {
+        self.1.namespaces.shift_remove(&Type::of::<N>())
+    }
+
+    /// Converts the set into an immutable [`FrozenSingletonSet`], which is
+    /// faster to read from.
+    ///
+    /// The frozen set can be turned back into a `SingletonSet` with
+    /// [`FrozenSingletonSet::thaw()`]. Only the values of the set's own
+    /// slots are kept; its namespaces, registrations and seals are dropped,
+    /// so the thawed set's slots are all unsealed.
+    ///
+    /// The frozen set is neither [`Send`] nor [`Sync`], and can't be turned
+    /// into a [`SyncFrozenSingletonSet`], because a `SingletonSet` doesn't
+    /// record which of its values are. A set to be shared between threads
+    /// has to be built with [`SyncFrozenSingletonSet::builder()`] instead.
+    ///
+    /// [`SyncFrozenSingletonSet::builder()`]: FrozenSingletonSet::builder()
+    pub fn freeze(self) -> FrozenSingletonSet {
+        FrozenSingletonSet::from_slots(self.0)
+    }
+
+    /// Converts the set into a [`JournaledSingletonSet`], which records up
+    /// to `capacity` changes so they can be undone and redone.
+    ///
+    /// The journal starts out empty, so the set's current contents can't be
+    /// undone.
+    pub fn journaled(self, capacity: usize) -> JournaledSingletonSet {
+        JournaledSingletonSet::new(self, capacity)
+    }
+
+    /// Seals the slot of the specified type, so that its value can't be
+    /// changed, replaced or removed until the slot is unsealed.
+    ///
+    /// Methods that would change a sealed slot and return an [`Option`],
+    /// like [`.try_as_mut()`] and [`.get_by_type_mut()`], return [`None`],
+    /// and [`.iter_mut()`] skips the slot. The `checked_` methods, like
+    /// [`.checked_insert()`], return a [`Sealed`] error naming the slot's
+    /// [`Type`]. Every other method that would change the slot, like
+    /// [`.insert()`], [`.remove()`], [`.with_mut()`] and [`.clear()`],
+    /// panics.
+    ///
+    /// Returns the [`UnsealToken`] that [`.unseal()`] requires, or [`None`]
+    /// if the slot is empty or already sealed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::{SingletonSet, Type};
+    ///
+    /// struct Config {
+    ///     verbose: bool,
+    /// }
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.insert(Config { verbose: false });
+    ///
+    /// let token = set.seal::<Config>().unwrap();
+    /// assert!(set.try_as_mut::<Config>().is_none());
+    ///
+    /// let err = set.checked_insert(Config { verbose: true }).err().unwrap();
+    /// assert_eq!(err.to_type(), Type::of::<Config>());
+    /// assert!(set.checked_remove::<Config>().is_err());
+    ///
+    /// assert!(set.unseal::<Config>(&token));
+    /// set.try_as_mut::<Config>().unwrap().verbose = true;
+    /// ```
+    ///
+    /// [`.try_as_mut()`]: Self::try_as_mut()
+    /// [`.get_by_type_mut()`]: Self::get_by_type_mut()
+    /// [`.iter_mut()`]: Self::iter_mut()
+    /// [`.checked_insert()`]: Self::checked_insert()
+    /// [`.insert()`]: Self::insert()
+    /// [`.remove()`]: Self::remove()
+    /// [`.with_mut()`]: Self::with_mut()
+    /// [`.clear()`]: Self::clear()
+    /// [`.unseal()`]: Self::unseal()
+    pub fn seal<T>(&mut self) -> Option<UnsealToken>
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if !self.contains_type(&t) || self.1.sealed.contains_key(&t) {
+            return None;
+        }
+
+        let token = UnsealToken::new();
+        self.1.sealed.insert(t, token.id());
+        Some(token)
+    }
+
+    /// Seals every slot that holds a value and isn't already sealed, and
+    /// returns the [`UnsealToken`] that unseals them.
+    pub fn seal_all(&mut self) -> UnsealToken {
+        let token = UnsealToken::new();
+        for t in self.0.keys() {
+            self.1.sealed.entry(*t).or_insert(token.id());
+        }
+
+        token
+    }
+
+    /// Returns true if the slot of the specified type is sealed.
+    pub fn is_sealed<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.1.sealed.contains_key(&Type::of::<T>())
     }
 
-    /// This is an alias for [`Self::try_as_ref()`]
-    pub fn try_get<T>(&self) -> Option<&T>
+    /// Unseals the slot of the specified type, if it was sealed along with
+    /// `token`.
+    ///
+    /// Returns true if the slot was unsealed.
+    pub fn unseal<T>(&mut self, token: &UnsealToken) -> bool
     where
         T: 'static,
     {
-        self.try_as_ref()
+        let t = Type::of::<T>();
+        if self.1.sealed.get(&t) != Some(&token.id()) {
+            return false;
+        }
+
+        self.1.sealed.shift_remove(&t);
+        true
     }
 
-    /// This is an alias for [`Self::as_mut()`]
-    pub fn get_mut<T>(&mut self) -> &mut T
-    where
-        T: 'static + Default,
-    {
-        self.as_mut()
+    /// Unseals every slot that was sealed along with `token`, and returns
+    /// how many were unsealed.
+    pub fn unseal_all(&mut self, token: &UnsealToken) -> usize {
+        let before = self.1.sealed.len();
+        self.1.sealed.retain(|_, id| *id != token.id());
+        before - self.1.sealed.len()
     }
 
-    /// Returns a mutable reference to the value of the specified type,
-    /// if it exists.
+    /// Inserts a value into the inferred type's slot, unless the slot is
+    /// sealed.
     ///
-    /// This method does not insert an element into the set, so it can be
-    /// used with types that do not implement [`Default`].
-    #[doc(alias = "try_get_mut()")]
-    pub fn try_as_mut<T>(&mut self) -> Option<&mut T>
+    /// If the slot already held a value, that value is returned. Like
+    /// [`.insert()`], a value rejected by the type's validator is dropped
+    /// and `Ok(None)` is returned.
+    ///
+    /// [`.insert()`]: Self::insert()
+    pub fn checked_insert<T>(&mut self, value: T) -> Result<Option<T>, Sealed>
     where
         T: 'static,
     {
-        self.0
-            .get_mut(&Type::of::<T>())
-            .and_then(|boxed| boxed.downcast_mut::<T>())
+        self.1.check_unsealed(&Type::of::<T>())?;
+        Ok(self.insert(value))
     }
 
-    /// This is an alias for [`Self::try_as_mut()`]
-    pub fn try_get_mut<T>(&mut self) -> Option<&mut T>
+    /// Removes the value from the inferred type's slot and returns it, if
+    /// it exists, unless the slot is sealed.
+    pub fn checked_remove<T>(&mut self) -> Result<Option<T>, Sealed>
     where
         T: 'static,
     {
-        self.try_as_mut()
+        self.1.check_unsealed(&Type::of::<T>())?;
+        Ok(self.remove())
     }
 
-    /// Returns an immutable reference to the value of the specified type,
-    /// inserting the provided value if the type isn't already in the set.
-    ///
-    /// If the type is already represented in the set, the provided value
-    /// is ignored.
-    #[doc(alias = "get_or_insert()")]
-    pub fn as_ref_or_insert<T>(&mut self, value: T) -> &T
+    /// Returns a mutable reference to the value of the specified type, if
+    /// it exists, unless the slot is sealed.
+    pub fn checked_as_mut<T>(&mut self) -> Result<Option<&mut T>, Sealed>
     where
         T: 'static,
     {
-        self.0
-            .entry(Type::of::<T>())
-            .or_insert(Box::new(value))
-            .downcast_ref::<T>()
-            // Safety: It should be safe to downcast a value back to its
-            // original type, so this `unwrap()` will never panic.
-            .unwrap()
+        self.1.check_unsealed(&Type::of::<T>())?;
+        Ok(self.try_as_mut())
     }
 
-    /// This is an alias for [`Self::as_ref_or_insert()`]
-    pub fn get_or_insert<T>(&mut self, value: T) -> &T
+    /// Calls a closure with the value of the corresponding type's slot,
+    /// inserting the default value first if the type isn't in the set,
+    /// unless the slot is sealed.
+    pub fn checked_with_mut<T, R>(&mut self, f: impl FnOnce(&mut T) -> R) -> Result<R, Sealed>
     where
-        T: 'static,
+        T: 'static + Default,
     {
-        self.as_ref_or_insert(value)
+        self.1.check_unsealed(&Type::of::<T>())?;
+        Ok(self.with_mut(f))
     }
 
-    /// Returns a mutable reference to the value of the specified type,
-    /// inserting the provided value if the type isn't already in the set.
+    /// Clears the set, removing all values, unless any slot is sealed.
     ///
-    /// If the type is already represented in the set, the provided value
-    /// is ignored. To avoid allocating memory for a default value that is
-    /// discarded, use [`.get_or_insert_with_mut()`].
+    /// If a slot is sealed, nothing is removed, and the error names the
+    /// first sealed slot.
+    pub fn checked_clear(&mut self) -> Result<(), Sealed> {
+        if let Some(t) = self.1.sealed.keys().next() {
+            self.1.check_unsealed(t)?;
+        }
+
+        self.clear();
+        Ok(())
+    }
+
+    /// Registers a validator for the values of the specified type.
     ///
-    /// [`.get_or_insert_with_mut()`]: Self::get_or_insert_with_mut()
-    #[doc(alias = "get_or_insert_mut()")]
-    pub fn as_mut_or_insert<T>(&mut self, value: T) -> &mut T
+    /// The validator runs whenever a value is inserted into the slot of `T`
+    /// by methods like [`.insert()`], whenever the empty slot is filled by
+    /// methods like [`.get_or_insert()`], and whenever the closure given to
+    /// methods like [`.with_mut()`] returns. Values borrowed mutably any
+    /// other way are not validated.
+    ///
+    /// A value that fails validation is added to the
+    /// [`.validation_report()`], and is handled according to the
+    /// [`ValidationPolicy`]. When a change is rejected, an inserted value is
+    /// dropped, and a value changed by a closure is restored to a clone of
+    /// the value from before the closure ran. The first value of an empty
+    /// slot has nothing to fall back on, so rejecting it panics.
+    ///
+    /// Any validator that was registered for the type before is replaced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use singletonset::SingletonSet;
+    ///
+    /// #[derive(Clone)]
+    /// struct PoolConfig {
+    ///     min: u32,
+    ///     max: u32,
+    /// }
+    ///
+    /// let mut set = SingletonSet::new();
+    /// set.register_validator(|config: &PoolConfig| match config.max >= config.min {
+    ///     true => Ok(()),
+    ///     false => Err("max must not be less than min"),
+    /// });
+    ///
+    /// set.insert(PoolConfig { min: 1, max: 8 });
+    /// set.with_mut_or_else(|| unreachable!(), |config: &mut PoolConfig| config.max = 0);
+    ///
+    /// assert_eq!(set.get::<PoolConfig>().max, 8);
+    /// assert_eq!(set.validation_report().len(), 1);
+    /// ```
+    ///
+    /// [`.insert()`]: Self::insert()
+    /// [`.get_or_insert()`]: Self::get_or_insert()
+    /// [`.with_mut()`]: Self::with_mut()
+    /// [`.validation_report()`]: Self::validation_report()
+    pub fn register_validator<T, E>(&mut self, validate: impl Fn(&T) -> Result<(), E> + 'static)
     where
-        T: 'static,
+        T: Clone + 'static,
+        E: Into<ValidationError>,
     {
-        self.0
-            .entry(Type::of::<T>())
-            .or_insert(Box::new(value))
-            .downcast_mut::<T>()
-            // Safety: The key exists and the type must be correct
-            .unwrap()
+        self.1.validators.register(validate);
     }
 
-    /// This is an alias for [`.as_mut_or_insert(value)`]
+    /// Registers a validator for the values of a type that can't be cloned.
     ///
-    /// [`.as_mut_or_insert(value)`]: Self::as_mut_or_insert()
-    pub fn get_or_insert_mut<T>(&mut self, value: T) -> &mut T
-    where
+    /// This works like [`.register_validator()`], except that a value
+    /// changed by a closure can't be restored, so a failed change is always
+    /// kept and reported, as if the [`ValidationPolicy`] were
+    /// [`Report`]. Inserted values are still dropped when rejected.
+    ///
+    /// [`.register_validator()`]: Self::register_validator()
+    /// [`Report`]: ValidationPolicy::Report
+    pub fn register_validator_without_undo<T, E>(
+        &mut self,
+        validate: impl Fn(&T) -> Result<(), E> + 'static,
+    ) where
         T: 'static,
+        E: Into<ValidationError>,
     {
-        self.as_mut_or_insert(value)
+        self.1.validators.register_without_undo(validate);
     }
 
-    /// Returns an immutable reference to the value of the specified type,
-    /// inserting the return value of the provided method if the type isn't
-    /// already in the set.
-    #[doc(alias = "get_or_insert_mut()")]
-    pub fn as_ref_or_insert_with<T>(&mut self, default: impl FnOnce() -> T) -> &T
+    /// Removes the validator of the specified type, and returns true if it
+    /// had one.
+    pub fn unregister_validator<T>(&mut self) -> bool
     where
         T: 'static,
     {
-        self.0
-            .entry(Type::of::<T>())
-            .or_insert_with(|| Box::new(default()))
-            .downcast_ref::<T>()
-            // Safety: The key exists and the type must be correct
-            .unwrap()
+        self.1.validators.unregister(&Type::of::<T>())
     }
 
-    /// This is an alias for [`Self::as_ref_or_insert_with()`]
-    pub fn get_or_insert_with<T>(&mut self, default: impl FnOnce() -> T) -> &T
-    where
-        T: 'static,
-    {
-        self.as_ref_or_insert_with(default)
+    /// Returns what the set does with changes that fail validation.
+    pub fn validation_policy(&self) -> ValidationPolicy {
+        self.1.validators.policy()
     }
 
-    /// Returns a mutable reference to the value of the specified type,
-    /// inserting the return value of the provided method if the type isn't
-    /// already in the set.
-    #[doc(alias = "get_or_insert_with_mut()")]
-    pub fn as_mut_or_insert_with<T>(&mut self, default: impl FnOnce() -> T) -> &mut T
+    /// Sets what the set does with changes that fail validation.
+    pub fn set_validation_policy(&mut self, policy: ValidationPolicy) {
+        self.1.validators.set_policy(policy);
+    }
+
+    /// Returns every validation failure the set has recorded.
+    pub fn validation_report(&self) -> &ValidationReport {
+        self.1.validators.report()
+    }
+
+    /// Returns every validation failure the set has recorded, and empties
+    /// its report.
+    pub fn take_validation_report(&mut self) -> ValidationReport {
+        self.1.validators.take_report()
+    }
+
+    /// Returns an immutable view of the set that exposes every type.
+    pub fn view(&self) -> SingletonSetView<'_> {
+        SingletonSetView::new(self, None)
+    }
+
+    /// Returns an immutable view of the set that exposes only the types in
+    /// the [`Bundle`].
+    ///
+    /// Every other type appears to be absent from the view.
+    pub fn restricted<B>(&self) -> SingletonSetView<'_>
     where
-        T: 'static,
+        B: Bundle,
     {
-        self.0
-            .entry(Type::of::<T>())
-            .or_insert_with(|| Box::new(default()))
-            .downcast_mut::<T>()
-            // Safety: The key exists and the type must be correct
-            .unwrap()
+        SingletonSetView::new(self, Some(B::types()))
     }
 
-    /// This is an alias for [`.as_mut_or_insert_with(default)`]
-    ///
-    /// [`.as_mut_or_insert_with(default)`]: Self::as_mut_or_insert_with()
-    pub fn get_or_insert_with_mut<T>(&mut self, default: impl FnOnce() -> T) -> &mut T
+    /// Returns an immutable view of the set that exposes only the types in
+    /// `allowed`.
+    pub fn restricted_to(&self, allowed: &TypeSet) -> SingletonSetView<'_> {
+        SingletonSetView::new(self, Some(allowed.clone()))
+    }
+
+    /// Returns a mutable view of the set that exposes, and can change, only
+    /// the slots of the types in the [`Bundle`].
+    pub fn restricted_mut<B>(&mut self) -> SingletonSetViewMut<'_>
     where
-        T: 'static,
+        B: Bundle,
     {
-        self.as_mut_or_insert_with(default)
+        SingletonSetViewMut::new(self, B::types())
     }
 
-    /// Returns an iterator that visits each [`Type`] in the set in random order.
+    /// Returns a mutable view of the set that exposes, and can change, only
+    /// the slots of the types in `allowed`.
+    pub fn restricted_to_mut(&mut self, allowed: &TypeSet) -> SingletonSetViewMut<'_> {
+        SingletonSetViewMut::new(self, allowed.clone())
+    }
+
+    /// Replaces the slot of `t` with `slot`, placed at its index, and returns
+    /// the slot it replaced, along with the index that slot had.
     ///
-    /// The random order is inherited from the internal hash map used to
-    /// store the elements, but may change in the future.
-    pub fn types(&self) -> Types<'_> {
-        Types(self.0.keys())
+    /// The index is clamped to the length of the set. Neither the seal of
+    /// the slot nor the new value is checked, so callers must check that the
+    /// slot is unsealed, and [`admit`] a new value, first.
+    ///
+    /// [`admit`]: Extras::admit()
+    pub(crate) fn swap_slot(
+        &mut self,
+        t: Type,
+        slot: Option<(usize, Slot)>,
+    ) -> Option<(usize, Slot)> {
+        if slot.is_some() {
+            self.1.inserted(&t);
+        } else if self.contains_type(&t) {
+            self.1.removed(&t);
+        }
+
+        let old = self.0.shift_remove_full(&t).map(|(i, _, slot)| (i, slot));
+
+        if let Some((index, slot)) = slot {
+            self.0.shift_insert(index.min(self.0.len()), t, slot);
+        }
+
+        old
+    }
+}
+
+impl<'a> IntoIterator for &'a SingletonSet {
+    type Item = (&'a Type, &'a dyn Any);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut SingletonSet {
+    type Item = (&'a Type, &'a mut dyn Any);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
     }
 }
 
@@ -464,7 +2268,7 @@ where
     #[doc(alias = "get_mut()")]
     fn as_ref(&self) -> &T {
         self.try_as_ref()
-            .expect(".try_as_ref() or .as_mut() should be used if the slot might be empty")
+            .unwrap_or_else(|| slot_panic(&Type::of::<T>(), MISSING))
     }
 }
 
@@ -483,13 +2287,29 @@ where
 }
 
 /// An iterator of the [`Type`]s in a [`SingletonSet`].
-pub struct Types<'a>(indexmap::map::Keys<'a, Type, Box<dyn Any>>);
+pub struct Types<'a>(TypesInner<'a>);
+
+enum TypesInner<'a> {
+    Map(indexmap::map::Keys<'a, Type, Slot>),
+    Bag(indexmap::map::Keys<'a, Type, Vec<Slot>>),
+    Namespaces(indexmap::map::Keys<'a, Type, SingletonSet>),
+    Persistent(indexmap::map::Keys<'a, Type, persistent::PersistentSlot>),
+    Slice(std::slice::Iter<'a, Type>),
+    Allowed(indexmap::map::Keys<'a, Type, Slot>, &'a TypeSet),
+}
 
 impl<'a> Iterator for Types<'a> {
     type Item = &'a Type;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        match &mut self.0 {
+            TypesInner::Map(keys) => keys.next(),
+            TypesInner::Bag(keys) => keys.next(),
+            TypesInner::Namespaces(keys) => keys.next(),
+            TypesInner::Persistent(keys) => keys.next(),
+            TypesInner::Slice(iter) => iter.next(),
+            TypesInner::Allowed(keys, allowed) => keys.find(|t| allowed.contains_type(t)),
+        }
     }
 }
 
@@ -508,10 +2328,23 @@ impl<'a> Iterator for Types<'a> {
 /// the first open angle bracket (`<`) and the nearest colon (`:`) to the
 /// left of it.
 ///
+/// For anything more detailed, [`.parsed_name()`] breaks the full name
+/// down into its crate, module path, base name and generic arguments.
+///
+/// A `Type` also records the memory layout of the type, which is available
+/// from [`.size()`], [`.align()`] and [`.needs_drop()`], unless the type
+/// was created with [`Type::of_unsized()`]. Like the name, the
+/// layout is only informational. Two `Type`s are equal if and only if their
+/// [`TypeId`]s are equal.
+///
 /// [`.as_str()`]: Self::as_str()
 /// [`.as_name()`]: Self::as_name()
+/// [`.parsed_name()`]: Self::parsed_name()
+/// [`.size()`]: Self::size()
+/// [`.align()`]: Self::align()
+/// [`.needs_drop()`]: Self::needs_drop()
 #[derive(Clone, Copy, Debug, Eq)]
-pub struct Type(TypeId, &'static str);
+pub struct Type(TypeId, &'static str, Option<Layout>, bool);
 
 impl Type {
     /// Creates a new `Type`
@@ -519,7 +2352,51 @@ impl Type {
     where
         T: 'static,
     {
-        Type(TypeId::of::<T>(), std::any::type_name::<T>())
+        Type(
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+            Some(Layout::new::<T>()),
+            std::mem::needs_drop::<T>(),
+        )
+    }
+
+    /// Creates a new `Type` for the type of the given value.
+    ///
+    /// This is useful when the type can be inferred but not easily named.
+    pub fn of_val<T>(value: &T) -> Self
+    where
+        T: 'static,
+    {
+        let _ = value;
+        Type::of::<T>()
+    }
+
+    /// Creates a new `Type` for a type that may be unsized, such as `str`,
+    /// `[T]` or `dyn Trait`.
+    ///
+    /// The `Type` is equal to the one returned by [`Type::of()`] for the
+    /// same sized type. Since there is no way to tell whether `T` is sized,
+    /// the `Type` never records a layout, even when `T` is sized:
+    /// [`.try_size()`] and [`.try_align()`] always return [`None`], and
+    /// [`.size()`] and [`.align()`] panic. Use [`Type::of()`] for sized
+    /// types whose layout is needed.
+    ///
+    /// [`.size()`]: Self::size()
+    /// [`.align()`]: Self::align()
+    /// [`.try_size()`]: Self::try_size()
+    /// [`.try_align()`]: Self::try_align()
+    pub fn of_unsized<T>() -> Self
+    where
+        T: ?Sized + 'static,
+    {
+        Type(
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+            None,
+            // Whether an unsized value needs to be dropped depends on the
+            // value, so this has to assume that it does.
+            true,
+        )
     }
 
     /// Returns a [`TypeId`] representing the type uniquely among all other
@@ -570,6 +2447,81 @@ impl Type {
 
         self.1[from_index..to_index].to_string()
     }
+
+    /// Returns the name of the type, parsed into its parts.
+    ///
+    /// Unlike [`.as_name()`], the parsed name keeps the generic arguments
+    /// of the type, so `Vec<Foo>` and `Vec<Bar>` can be told apart. See
+    /// [`TypeName`] for details.
+    ///
+    /// [`.as_name()`]: Self::as_name()
+    pub fn parsed_name(&self) -> TypeName<'static> {
+        TypeName::parse(self.1)
+    }
+
+    /// Returns the size of the type in bytes, as reported by
+    /// [`std::mem::size_of()`].
+    ///
+    /// # Safety
+    ///
+    /// This method panics if the `Type` was created with
+    /// [`Type::of_unsized()`]. If this is not acceptable, use
+    /// [`.try_size()`].
+    ///
+    /// [`.try_size()`]: Self::try_size()
+    pub fn size(&self) -> usize {
+        self.try_size()
+            .expect(".try_size() should be used if the type might be unsized")
+    }
+
+    /// Returns the size of the type in bytes, as reported by
+    /// [`std::mem::size_of()`], or [`None`] if the `Type` was created with
+    /// [`Type::of_unsized()`].
+    pub fn try_size(&self) -> Option<usize> {
+        self.2.map(|layout| layout.size())
+    }
+
+    /// Returns the minimum alignment of the type in bytes, as reported by
+    /// [`std::mem::align_of()`].
+    ///
+    /// # Safety
+    ///
+    /// This method panics if the `Type` was created with
+    /// [`Type::of_unsized()`]. If this is not acceptable, use
+    /// [`.try_align()`].
+    ///
+    /// [`.try_align()`]: Self::try_align()
+    pub fn align(&self) -> usize {
+        self.try_align()
+            .expect(".try_align() should be used if the type might be unsized")
+    }
+
+    /// Returns the minimum alignment of the type in bytes, as reported by
+    /// [`std::mem::align_of()`], or [`None`] if the `Type` was created with
+    /// [`Type::of_unsized()`].
+    pub fn try_align(&self) -> Option<usize> {
+        self.2.map(|layout| layout.align())
+    }
+
+    /// Returns true if dropping a value of the type runs any code, as
+    /// reported by [`std::mem::needs_drop()`].
+    ///
+    /// Like the function it comes from, this may return true for some types
+    /// that don't actually need to be dropped. It always returns true if
+    /// the `Type` was created with [`Type::of_unsized()`].
+    pub fn needs_drop(&self) -> bool {
+        self.3
+    }
+
+    /// Returns the [`StableKey`] of the type, if it implements
+    /// [`StableTypeKey`] and has been registered.
+    ///
+    /// Unlike the name and the [`TypeId`], the stable key is chosen by the
+    /// author of the type, so it can be stored on disk or sent to another
+    /// process built from different source.
+    pub fn stable_key(&self) -> Option<StableKey> {
+        stable_key::stable_key_of(self)
+    }
 }
 
 impl AsRef<str> for Type {
@@ -714,4 +2666,283 @@ mod tests {
         assert!(iter.next().is_some());
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn singletonset_type_set_matches_types() {
+        let mut set = SingletonSet::new();
+
+        set.get_mut::<u8>();
+        set.get_mut::<String>();
+
+        let types = set.type_set();
+
+        assert_eq!(types.len(), 2);
+        assert!(types.contains::<u8>());
+        assert!(types.contains::<String>());
+        assert!(!types.contains::<u16>());
+        assert!(types.iter().eq(set.types()));
+    }
+
+    #[test]
+    fn singletonset_holds_unsized_values() {
+        trait Greet {
+            fn greet(&self) -> String;
+        }
+
+        struct English;
+
+        impl Greet for English {
+            fn greet(&self) -> String {
+                "Hello".to_string()
+            }
+        }
+
+        let mut set = SingletonSet::new();
+        set.insert_unsized::<dyn Greet>(Box::new(English));
+        set.insert_unsized::<[u8]>(vec![1, 2].into());
+        set.insert(3u8);
+
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.get_unsized::<dyn Greet>().unwrap().greet(), "Hello");
+        assert_eq!(set.get::<u8>(), &3);
+
+        set.get_unsized_mut::<[u8]>().unwrap()[0] = 9;
+        assert_eq!(set.get_unsized::<[u8]>(), Some(&[9, 2][..]));
+        assert_eq!(set.get_unsized::<str>(), None);
+
+        let old = set.insert_unsized::<[u8]>(vec![5].into());
+        assert_eq!(old.as_deref(), Some(&[9, 2][..]));
+        assert_eq!(set.remove_unsized::<[u8]>().as_deref(), Some(&[5][..]));
+        assert!(!set.contains_type(&Type::of_unsized::<[u8]>()));
+    }
+
+    #[test]
+    fn singletonset_keeps_slots_removed_through_the_other_storage() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert_unsized::<u16>(Box::new(2));
+
+        assert_eq!(set.remove_unsized::<u8>(), None);
+        assert_eq!(set.remove::<u16>(), None);
+        assert_eq!(set.len(), 2);
+
+        assert_eq!(set.remove::<u8>(), Some(1));
+        assert_eq!(set.remove_unsized::<u16>(), Some(Box::new(2)));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "insert_unsized")]
+    fn singletonset_refuses_sized_reads_of_unsized_slots() {
+        let mut set = SingletonSet::new();
+        set.insert_unsized::<u32>(Box::new(1));
+        set.get_or_insert(2u32);
+    }
+
+    #[test]
+    fn singletonset_can_be_used_without_static_types() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert("two".to_string());
+
+        let t = Type::of_val(&0u8);
+        assert_eq!(set.get_by_type(&t).unwrap().downcast_ref(), Some(&1u8));
+        assert!(set.get_by_type(&Type::of::<u16>()).is_none());
+
+        for (_, value) in set.iter_mut() {
+            if let Some(n) = value.downcast_mut::<u8>() {
+                *n += 1;
+            }
+        }
+        assert_eq!(set.get::<u8>(), &2);
+
+        let copy: Vec<_> = set.iter().map(|(t, _)| *t).collect();
+        assert!(copy.iter().eq(set.types()));
+        assert_eq!(set.iter().len(), 2);
+
+        let old = set.insert_boxed(t, Box::new(5u8)).unwrap().unwrap();
+        assert_eq!(old.downcast_ref(), Some(&2u8));
+
+        let err = set.insert_boxed(t, Box::new(6u16)).unwrap_err();
+        assert_eq!(err.expected(), t);
+        assert_eq!(err.to_string(), "boxed value is not a u8");
+        assert_eq!(set.get::<u8>(), &5);
+    }
+
+    #[test]
+    fn singletonset_shares_values() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert_shared(2u16);
+
+        assert!(set.is_shared::<u16>());
+        assert!(!set.is_shared::<u8>());
+        assert_eq!(set.get_arc::<u8>(), None);
+        assert_eq!(
+            set.slot_kinds().map(|(_, kind)| kind).collect::<Vec<_>>(),
+            [SlotKind::Owned, SlotKind::Shared]
+        );
+
+        // The value can be changed while the set holds the only `Arc`.
+        *set.get_mut::<u16>() += 1;
+
+        let arc = set.get_arc::<u16>().unwrap();
+        let weak = set.downgrade::<u16>().unwrap();
+        assert_eq!(*arc, 3);
+        assert_eq!(set.get::<u16>(), &3);
+        assert_eq!(set.try_get_mut::<u16>(), None);
+        assert_eq!(set.iter_mut().count(), 1);
+
+        // A value that is still shared can't be removed, so it stays put.
+        assert_eq!(set.remove::<u16>(), None);
+        assert!(set.contains::<u16>());
+        drop(arc);
+        assert_eq!(set.remove::<u16>(), Some(3));
+        assert_eq!(weak.upgrade(), None);
+
+        set.insert_shared(4u16);
+        assert_eq!(set.insert(5u16), Some(4));
+        assert_eq!(set.insert_shared(6u16).as_deref(), Some(&5));
+        assert_eq!(set.remove::<u16>(), Some(6));
+    }
+
+    #[test]
+    fn singletonset_never_moves_pinned_values() {
+        let mut set = SingletonSet::new();
+        let address = &*set.insert_pinned(String::from("pinned")) as *const String;
+
+        for i in 0..64u64 {
+            set.insert_shared(i);
+            set.insert(i as u8);
+        }
+
+        assert_eq!(set.slot_kind(&Type::of::<String>()), Some(SlotKind::Pinned));
+        assert_eq!(set.get::<String>(), "pinned");
+        assert_eq!(set.try_get_mut::<String>(), None);
+        assert!(set.get_by_type_mut(&Type::of::<String>()).is_none());
+
+        let mut pinned = set.get_pinned_mut::<String>().unwrap();
+        pinned.push('!');
+        assert_eq!(&*pinned as *const String, address);
+
+        // Only pinned slots can be removed as pinned values.
+        assert_eq!(set.remove_pinned::<u8>(), None);
+        assert!(set.contains::<u8>());
+
+        let removed = set.remove_pinned::<String>().unwrap();
+        assert_eq!(&*removed as *const String, address);
+        assert_eq!(*removed, "pinned!");
+
+        // Removing a pinned value by value leaves it where it is.
+        set.insert_pinned(1u32);
+        assert_eq!(set.remove::<u32>(), None);
+        assert_eq!(set.get::<u32>(), &1);
+
+        // Replacing a pinned value drops it in place.
+        assert_eq!(set.insert(2u32), None);
+        assert_eq!(set.remove::<u32>(), Some(2));
+    }
+
+    #[test]
+    fn singletonset_namespaces_are_isolated() {
+        struct Outer;
+        struct Inner;
+
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.namespace_mut::<Outer>().insert(2u8);
+        set.namespace_mut::<Outer>()
+            .namespace_mut::<Inner>()
+            .insert(3u8);
+
+        assert_eq!(set.get::<u8>(), &1);
+        assert_eq!(set.namespace::<Outer>().unwrap().get::<u8>(), &2);
+        assert_eq!(set.namespace::<Inner>().map(SingletonSet::len), None);
+        assert!(set.namespaces().eq([Type::of::<Outer>()].iter()));
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.len_recursive(), 3);
+
+        set.clear();
+        assert_eq!(set.len_recursive(), 2);
+
+        let outer = set.remove_namespace::<Outer>().unwrap();
+        assert_eq!(outer.namespace::<Inner>().unwrap().get::<u8>(), &3);
+        assert_eq!(set.namespaces().count(), 0);
+        assert!(set.remove_namespace::<Outer>().is_none());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn singletonset_traces_slot_events() {
+        use std::{
+            panic::{catch_unwind, AssertUnwindSafe},
+            sync::{Arc, Mutex},
+        };
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata, Subscriber,
+        };
+
+        struct Fields(String);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0 += &format!(" {}={:?}", field.name(), value);
+            }
+        }
+
+        struct Capture(Arc<Mutex<Vec<String>>>);
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let mut fields = Fields(format!("span {}:", span.metadata().name()));
+                span.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = Fields(event.metadata().level().to_string());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Capture(log.clone()), || {
+            let mut set = SingletonSet::new();
+            set.insert(1u8);
+            set.get_or_insert_with(|| 2u16);
+            set.remove::<u8>();
+            let _token = set.seal::<u16>().unwrap();
+
+            let mut set = AssertUnwindSafe(set);
+            assert!(catch_unwind(|| *set.get::<u32>()).is_err());
+            assert!(catch_unwind(move || set.insert(3u16)).is_err());
+        });
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "DEBUG message=insert type_name=\"u8\"",
+                "span initialize: type_name=\"u16\"",
+                "DEBUG message=initialize type_name=\"u16\"",
+                "DEBUG message=remove type_name=\"u8\"",
+                "ERROR message=panicking lookup type_name=\"u32\"",
+                "ERROR message=change to a sealed slot type_name=\"u16\"",
+            ]
+        );
+    }
 }
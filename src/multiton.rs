@@ -0,0 +1,232 @@
+use indexmap::IndexMap;
+
+use crate::{slot::Slot, SingletonSet, Type, Types, TypesInner};
+
+/// A companion to [`SingletonSet`] that holds any number of values of each
+/// type.
+///
+/// Values are grouped by their [`Type`], and the values of each type are
+/// kept in the order they were pushed. This is useful for collections like
+/// "every event handler" or "every middleware", where each value is
+/// looked up by its type but there may be more than one.
+///
+/// A `MultitonSet` can be made from a `SingletonSet`, with each value
+/// becoming the only value of its type, and turned back into one by keeping
+/// either the first or the last value of each type.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::MultitonSet;
+///
+/// struct Handler(&'static str);
+///
+/// let mut bag = MultitonSet::new();
+/// bag.push(Handler("log"));
+/// bag.push(Handler("audit"));
+/// bag.push(8080u16);
+///
+/// assert_eq!(bag.len_of::<Handler>(), 2);
+/// assert_eq!(
+///     bag.iter::<Handler>().map(|h| h.0).collect::<Vec<_>>(),
+///     ["log", "audit"]
+/// );
+///
+/// let set = bag.into_last();
+/// assert_eq!(set.get::<Handler>().0, "audit");
+/// assert_eq!(set.get::<u16>(), &8080);
+/// ```
+#[doc(alias = "TypeBag")]
+#[derive(Debug, Default)]
+pub struct MultitonSet(IndexMap<Type, Vec<Slot>>);
+
+impl MultitonSet {
+    /// Creates an empty `MultitonSet`.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        MultitonSet(IndexMap::new())
+    }
+
+    /// Returns the number of values the set holds, of every type.
+    pub fn len(&self) -> usize {
+        self.0.values().map(Vec::len).sum()
+    }
+
+    /// Returns true if the set holds no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of values of the specified type.
+    pub fn len_of<T>(&self) -> usize
+    where
+        T: 'static,
+    {
+        self.0.get(&Type::of::<T>()).map_or(0, Vec::len)
+    }
+
+    /// Returns true if the set holds at least one value of the specified
+    /// type.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.0.contains_key(&Type::of::<T>())
+    }
+
+    /// Removes every value from the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Adds a value after any other values of its type.
+    pub fn push<T>(&mut self, value: T)
+    where
+        T: 'static,
+    {
+        self.0
+            .entry(Type::of::<T>())
+            .or_default()
+            .push(Slot::new(value));
+    }
+
+    /// Returns an iterator over the values of the specified type, in the
+    /// order they were pushed.
+    pub fn iter<T>(&self) -> impl Iterator<Item = &T>
+    where
+        T: 'static,
+    {
+        self.0
+            .get(&Type::of::<T>())
+            .into_iter()
+            .flatten()
+            .filter_map(Slot::downcast_ref)
+    }
+
+    /// Returns an iterator over mutable references to the values of the
+    /// specified type, in the order they were pushed.
+    ///
+    /// Values that came from a pinned or shared slot of a [`SingletonSet`]
+    /// are skipped if they can't be borrowed mutably.
+    pub fn iter_mut<T>(&mut self) -> impl Iterator<Item = &mut T>
+    where
+        T: 'static,
+    {
+        self.0
+            .get_mut(&Type::of::<T>())
+            .into_iter()
+            .flatten()
+            .filter_map(Slot::downcast_mut)
+    }
+
+    /// Removes every value of the specified type from the set, and returns
+    /// them in the order they were pushed.
+    ///
+    /// Values that came from a pinned slot of a [`SingletonSet`], or from a
+    /// shared slot whose value is still held elsewhere, can't be returned
+    /// by value, so they are dropped instead.
+    pub fn drain<T>(&mut self) -> impl Iterator<Item = T>
+    where
+        T: 'static,
+    {
+        self.0
+            .shift_remove(&Type::of::<T>())
+            .into_iter()
+            .flatten()
+            .filter_map(Slot::into_inner)
+    }
+
+    /// Returns an iterator that visits each [`Type`] in the set, in the
+    /// order each type's first value was pushed.
+    pub fn types(&self) -> Types<'_> {
+        Types(TypesInner::Bag(self.0.keys()))
+    }
+
+    /// Converts the set into a [`SingletonSet`] holding the first value of
+    /// each type, and drops the rest.
+    pub fn into_first(self) -> SingletonSet {
+        self.into_singleton_set(|slots| slots.into_iter().next())
+    }
+
+    /// Converts the set into a [`SingletonSet`] holding the last value of
+    /// each type, and drops the rest.
+    pub fn into_last(self) -> SingletonSet {
+        self.into_singleton_set(|mut slots| slots.pop())
+    }
+
+    fn into_singleton_set(self, mut pick: impl FnMut(Vec<Slot>) -> Option<Slot>) -> SingletonSet {
+        SingletonSet(
+            self.0
+                .into_iter()
+                .filter_map(|(t, slots)| pick(slots).map(|slot| (t, slot)))
+                .collect(),
+            Default::default(),
+        )
+    }
+}
+
+/// Only the values in the set's own slots are converted. Everything else
+/// it holds is dropped: its namespaces along with their values, its debug
+/// formatters, memory measurements, computed values, validators and
+/// statistics, and its seals, so the values of sealed slots can be changed
+/// once they are in the `MultitonSet`.
+impl From<SingletonSet> for MultitonSet {
+    /// Converts a [`SingletonSet`] into a `MultitonSet` in which the value
+    /// of each slot is the only value of its type, dropping everything else
+    /// the set has registered.
+    fn from(set: SingletonSet) -> Self {
+        MultitonSet(set.0.into_iter().map(|(t, slot)| (t, vec![slot])).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiton_set_groups_values_by_type() {
+        let mut bag = MultitonSet::new();
+        bag.push(1u8);
+        bag.push("one");
+        bag.push(2u8);
+        bag.push(3u8);
+
+        assert_eq!(bag.len(), 4);
+        assert_eq!(bag.len_of::<u8>(), 3);
+        assert_eq!(bag.len_of::<u16>(), 0);
+        assert!(bag
+            .types()
+            .eq([Type::of::<u8>(), Type::of::<&str>()].iter()));
+
+        for n in bag.iter_mut::<u8>() {
+            *n *= 10;
+        }
+        assert_eq!(bag.drain::<u8>().collect::<Vec<_>>(), [10, 20, 30]);
+        assert!(!bag.contains::<u8>());
+        assert_eq!(bag.iter::<u8>().count(), 0);
+        assert_eq!(bag.len(), 1);
+    }
+
+    #[test]
+    fn multiton_set_converts_to_and_from_singleton_set() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert_shared("shared");
+
+        let mut bag = MultitonSet::from(set);
+        bag.push(2u8);
+        bag.push(3u8);
+        bag.push(4u16);
+
+        let first = MultitonSet::from(bag.into_first());
+        assert_eq!(first.iter::<u8>().collect::<Vec<_>>(), [&1]);
+
+        let last = first.into_last();
+        assert_eq!(last.get::<u8>(), &1);
+        assert_eq!(last.get::<u16>(), &4);
+        assert!(last.is_shared::<&str>());
+    }
+}
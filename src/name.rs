@@ -0,0 +1,151 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+};
+
+use crate::Type;
+
+/// A function that formats a type-erased value.
+pub(crate) type FormatFn = fn(&dyn Any, &mut Formatter<'_>) -> std::fmt::Result;
+
+pub(crate) fn format_debug<T>(value: &dyn Any, f: &mut Formatter<'_>) -> std::fmt::Result
+where
+    T: Debug + 'static,
+{
+    match value.downcast_ref::<T>() {
+        Some(value) => Debug::fmt(value, f),
+        None => write!(f, "<{}>", Type::of::<T>()),
+    }
+}
+
+pub(crate) fn format_display<T>(value: &dyn Any, f: &mut Formatter<'_>) -> std::fmt::Result
+where
+    T: Display + 'static,
+{
+    match value.downcast_ref::<T>() {
+        Some(value) => Display::fmt(value, f),
+        None => write!(f, "<{}>", Type::of::<T>()),
+    }
+}
+
+/// The value in one slot of a [`SingletonSet`], formatted with the
+/// function registered for its type.
+///
+/// This is created with [`SingletonSet::format_by_type()`], and implements
+/// both [`Display`] and [`Debug`], which format the value the same way.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::format_by_type()`]: crate::SingletonSet::format_by_type()
+pub struct FormattedSlot<'a> {
+    pub(crate) value: &'a dyn Any,
+    pub(crate) format: FormatFn,
+}
+
+impl Display for FormattedSlot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.format)(self.value, f)
+    }
+}
+
+impl Debug for FormattedSlot<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (self.format)(self.value, f)
+    }
+}
+
+/// The error returned by [`SingletonSet::find_by_name()`] when a name does
+/// not identify exactly one type in the set.
+///
+/// [`SingletonSet::find_by_name()`]: crate::SingletonSet::find_by_name()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FindByNameError {
+    /// No type in the set has the name.
+    NotFound(String),
+    /// More than one type in the set has the name. Every matching [`Type`]
+    /// is listed, in the order of the set.
+    Ambiguous(String, Vec<Type>),
+}
+
+impl Display for FindByNameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FindByNameError::NotFound(name) => write!(f, "no type named `{}`", name),
+            FindByNameError::Ambiguous(name, types) => {
+                write!(f, "`{}` could be any of: ", name)?;
+
+                for (i, t) in types.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", t)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for FindByNameError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::SingletonSet;
+
+    use super::*;
+
+    mod a {
+        #[derive(Debug)]
+        pub struct Config(pub u8);
+    }
+
+    mod b {
+        pub struct Config;
+    }
+
+    #[test]
+    fn short_names_can_be_ambiguous() {
+        let mut set = SingletonSet::new();
+        set.insert(a::Config(1));
+        set.insert(b::Config);
+        set.insert(vec![1u8]);
+        assert_eq!(set.get::<a::Config>().0, 1);
+
+        let err = set.find_by_name("Config").unwrap_err();
+        assert_eq!(
+            err,
+            FindByNameError::Ambiguous(
+                "Config".to_string(),
+                vec![Type::of::<a::Config>(), Type::of::<b::Config>()]
+            )
+        );
+        assert!(err.to_string().starts_with("`Config` could be any of: "));
+
+        let full = std::any::type_name::<a::Config>();
+        assert_eq!(set.find_by_name(full), Ok(Type::of::<a::Config>()));
+        assert_eq!(set.find_by_name("Vec<u8>"), Ok(Type::of::<Vec<u8>>()));
+        assert_eq!(
+            set.find_by_name("Missing"),
+            Err(FindByNameError::NotFound("Missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn registered_formatters_print_slots() {
+        let mut set = SingletonSet::new();
+        set.insert(a::Config(7));
+        set.insert(b::Config);
+        set.insert(3u16);
+        set.register_debug::<a::Config>();
+        set.register_display::<u16>();
+
+        let shown: Vec<_> = set
+            .types()
+            .filter_map(|t| set.format_by_type(t))
+            .map(|slot| slot.to_string())
+            .collect();
+        assert_eq!(shown, ["Config(7)", "3"]);
+        assert!(set.format_by_type(&Type::of::<b::Config>()).is_none());
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    any::Any,
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
+
+use indexmap::IndexMap;
+
+use crate::{Type, Types, TypesInner};
+
+/// A value that can be shared between snapshots and sent to other threads.
+type SharedValue = dyn Any + Send + Sync;
+
+/// A slot of a [`PersistentSingletonSet`], along with how to clone its
+/// value when it has to stop being shared.
+#[derive(Clone)]
+pub(crate) struct PersistentSlot {
+    value: Arc<SharedValue>,
+    clone: fn(&SharedValue) -> Arc<SharedValue>,
+}
+
+impl PersistentSlot {
+    fn new<T>(value: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        PersistentSlot {
+            value: Arc::new(value),
+            clone: clone_value::<T>,
+        }
+    }
+
+    /// Returns the value, cloning it if a snapshot still shares it.
+    fn into_inner<T>(self) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.value
+            .downcast::<T>()
+            .ok()
+            .map(|value| Arc::try_unwrap(value).unwrap_or_else(|value| (*value).clone()))
+    }
+}
+
+fn clone_value<T>(value: &SharedValue) -> Arc<SharedValue>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let value: &T = value
+        .downcast_ref()
+        // Safety: This function is only stored alongside values of type
+        // `T`, so this `expect()` will never panic.
+        .expect("a persistent slot holds a value of its own type");
+    Arc::new(value.clone())
+}
+
+/// A [`SingletonSet`] whose snapshots are cheap.
+///
+/// Taking a [`.snapshot()`] of the set takes constant time, no matter how
+/// many values it holds: the snapshot shares the set's values instead of
+/// copying them. A value is cloned only the first time it is borrowed
+/// mutably after a snapshot was taken, and only if the snapshot still
+/// exists, so snapshots never see later changes to the set, and the set
+/// never sees changes to its snapshots.
+///
+/// This is useful for keeping old versions of a context around, such as
+/// for time-travel debugging, or for handing a read-only copy of it to
+/// another thread. Since values can be shared between threads, they must
+/// be [`Clone`], [`Send`] and [`Sync`].
+///
+/// # Example
+///
+/// ```
+/// use singletonset::PersistentSingletonSet;
+///
+/// let mut set = PersistentSingletonSet::new();
+/// set.insert(1u8);
+/// set.insert("Hello".to_string());
+///
+/// let snapshot = set.snapshot();
+/// set.get_mut::<String>().push_str(", world");
+/// set.insert(2u8);
+///
+/// assert_eq!(set.get::<String>(), "Hello, world");
+/// assert_eq!(snapshot.get::<String>(), "Hello");
+/// assert_eq!(snapshot.get::<u8>(), &1);
+/// ```
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`.snapshot()`]: Self::snapshot()
+#[derive(Clone, Default)]
+pub struct PersistentSingletonSet(Arc<IndexMap<Type, PersistentSlot>>);
+
+impl PersistentSingletonSet {
+    /// Creates an empty `PersistentSingletonSet`.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        PersistentSingletonSet(Arc::default())
+    }
+
+    /// Returns a copy of the set that shares its values, in constant time.
+    ///
+    /// Changes made to the set after the snapshot is taken are not visible
+    /// in the snapshot, and changes made to the snapshot are not visible in
+    /// the set. This is the same as calling [`.clone()`].
+    ///
+    /// [`.clone()`]: Clone::clone()
+    #[must_use]
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns the number of elements the set currently holds.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns true if the type is represented in the set.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.0.contains_key(&Type::of::<T>())
+    }
+
+    /// Returns true if the supplied [`Type`] is represented in the set.
+    pub fn contains_type(&self, t: &Type) -> bool {
+        self.0.contains_key(t)
+    }
+
+    /// Returns an iterator that visits each [`Type`] in the set, in the
+    /// order they were inserted.
+    pub fn types(&self) -> Types<'_> {
+        Types(TypesInner::Persistent(self.0.keys()))
+    }
+
+    /// Clears the set, removing all values.
+    ///
+    /// Snapshots of the set keep their values.
+    pub fn clear(&mut self) {
+        self.0 = Arc::default();
+    }
+
+    /// Inserts a value into the inferred type's slot.
+    ///
+    /// If the slot already held a value, that value is returned. It is
+    /// cloned if a snapshot still shares it.
+    pub fn insert<T>(&mut self, value: T) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.0)
+            .insert(Type::of::<T>(), PersistentSlot::new(value))
+            .and_then(PersistentSlot::into_inner)
+    }
+
+    /// Removes the value from the inferred type's slot and returns it, if
+    /// it exists.
+    ///
+    /// The value is cloned if a snapshot still shares it. The order of the
+    /// remaining types is preserved.
+    pub fn remove<T>(&mut self) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let t = Type::of::<T>();
+        if !self.contains_type(&t) {
+            return None;
+        }
+
+        Arc::make_mut(&mut self.0)
+            .shift_remove(&t)
+            .and_then(PersistentSlot::into_inner)
+    }
+
+    /// Returns an immutable reference to the value of the specified type,
+    /// if it exists.
+    pub fn try_get<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        self.0
+            .get(&Type::of::<T>())
+            .and_then(|slot| slot.value.downcast_ref())
+    }
+
+    /// Returns an immutable reference to the value of the specified type.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if there is no existing value for the given type.
+    /// If this is not acceptable, use [`.try_get()`] or [`.get_mut()`].
+    ///
+    /// [`.try_get()`]: Self::try_get()
+    /// [`.get_mut()`]: Self::get_mut()
+    pub fn get<T>(&self) -> &T
+    where
+        T: 'static,
+    {
+        self.try_get()
+            .expect(".try_get() or .get_mut() should be used if the slot might be empty")
+    }
+
+    /// Returns a mutable reference to the value of the specified type, if
+    /// it exists.
+    ///
+    /// If a snapshot shares the value, the set's copy of it is cloned first.
+    pub fn try_get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if !self.contains_type(&t) {
+            return None;
+        }
+
+        let slot = Arc::make_mut(&mut self.0).get_mut(&t)?;
+        if Arc::get_mut(&mut slot.value).is_none() {
+            slot.value = (slot.clone)(&*slot.value);
+        }
+
+        Arc::get_mut(&mut slot.value).and_then(|value| value.downcast_mut())
+    }
+
+    /// Returns a mutable reference to the value of the specified type,
+    /// inserting the default value first if the type isn't in the set.
+    ///
+    /// If a snapshot shares the value, the set's copy of it is cloned first.
+    pub fn get_mut<T>(&mut self) -> &mut T
+    where
+        T: Clone + Default + Send + Sync + 'static,
+    {
+        if !self.contains::<T>() {
+            self.insert(T::default());
+        }
+
+        self.try_get_mut()
+            // Safety: The slot was just checked or filled, and its value was
+            // made unique, so this `expect()` will never panic.
+            .expect("the slot holds a value that isn't shared")
+    }
+
+    /// Returns true if `self` and `other` share the value of the specified
+    /// type, which means that neither has changed it since one was made
+    /// from a snapshot of the other.
+    pub fn shares<T>(&self, other: &PersistentSingletonSet) -> bool
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+
+        match (self.0.get(&t), other.0.get(&t)) {
+            (Some(a), Some(b)) => Arc::ptr_eq(&a.value, &b.value),
+            _ => false,
+        }
+    }
+}
+
+impl Debug for PersistentSingletonSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PersistentSingletonSet")
+            .field(&self.0.keys())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_never_see_later_changes() {
+        let mut set = PersistentSingletonSet::new();
+        set.insert(vec![1u8]);
+        set.insert(2u16);
+
+        let snapshot = set.snapshot();
+        assert!(set.shares::<Vec<u8>>(&snapshot));
+
+        set.get_mut::<Vec<u8>>().push(2);
+        assert!(!set.shares::<Vec<u8>>(&snapshot));
+        assert!(set.shares::<u16>(&snapshot));
+
+        assert_eq!(set.remove::<u16>(), Some(2));
+        assert_eq!(set.insert(vec![3u8]), Some(vec![1, 2]));
+        set.insert("three");
+
+        assert_eq!(snapshot.get::<Vec<u8>>(), &[1]);
+        assert_eq!(snapshot.get::<u16>(), &2);
+        assert!(!snapshot.contains::<&str>());
+        assert!(set
+            .types()
+            .eq([Type::of::<Vec<u8>>(), Type::of::<&str>()].iter()));
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(snapshot.len(), 2);
+    }
+
+    #[test]
+    fn snapshots_can_be_sent_to_other_threads() {
+        let mut set = PersistentSingletonSet::new();
+        set.insert("Hello".to_string());
+
+        let snapshot = set.snapshot();
+        let handle = std::thread::spawn(move || snapshot.get::<String>().len());
+
+        set.get_mut::<String>().clear();
+        assert_eq!(handle.join().unwrap(), 5);
+        assert_eq!(set.get::<String>(), "");
+        assert_eq!(set.try_get_mut::<u8>(), None);
+    }
+}
@@ -0,0 +1,280 @@
+//! A program-wide list of every type that might be stored in a
+//! [`SingletonSet`].
+//!
+//! Types are added to the list with [`register_singleton!`], which can be
+//! called from any crate in the program. The list is put together when the
+//! program is linked, so every registered type is known before `main`
+//! starts, whether or not a value of that type has ever been inserted into
+//! a set.
+//!
+//! This module is only available with the `inventory` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use singletonset::{register_singleton, registry, stable_type_key, SingletonSet, Type};
+//!
+//! #[derive(Default)]
+//! struct Config {
+//!     retries: u8,
+//! }
+//!
+//! struct Session;
+//! stable_type_key!(Session = "app.session", 1);
+//!
+//! register_singleton!(Config, default);
+//! register_singleton!(Session, stable_key);
+//!
+//! assert!(registry::all().any(|r| r.to_type() == Type::of::<Session>()));
+//!
+//! let mut set = SingletonSet::new();
+//! set.populate_defaults();
+//!
+//! assert!(set.contains::<Config>());
+//! assert!(!set.contains::<Session>());
+//! ```
+//!
+//! [`SingletonSet`]: crate::SingletonSet
+//! [`register_singleton!`]: crate::register_singleton!
+
+use std::any::Any;
+
+use crate::{stable_key, StableKey, StableKeyConflict, StableTypeKey, Type};
+
+/// A type that was registered with [`register_singleton!`], along with what
+/// is known about it.
+///
+/// [`register_singleton!`]: crate::register_singleton!
+#[derive(Debug)]
+pub struct RegisteredType {
+    to_type: fn() -> Type,
+    default: Option<fn() -> Box<dyn Any>>,
+    stable_key: Option<StableKey>,
+}
+
+inventory::collect!(RegisteredType);
+
+// The constructors are only meant to be called by `register_singleton!`,
+// which passes the same type to each of them. Nothing else can check that
+// they are given the same type, so they are hidden.
+impl RegisteredType {
+    /// Creates the registration for the type `T`, with no metadata.
+    #[doc(hidden)]
+    pub const fn of<T>() -> Self
+    where
+        T: 'static,
+    {
+        RegisteredType {
+            to_type: Type::of::<T>,
+            default: None,
+            stable_key: None,
+        }
+    }
+
+    /// Records that `T`, which must be the registered type, can be created
+    /// with [`Default::default()`].
+    #[doc(hidden)]
+    pub const fn with_default<T>(mut self) -> Self
+    where
+        T: Default + 'static,
+    {
+        self.default = Some(boxed_default::<T>);
+        self
+    }
+
+    /// Records the [`StableKey`] of `T`, which must be the registered type.
+    #[doc(hidden)]
+    pub const fn with_stable_key<T>(self) -> Self
+    where
+        T: StableTypeKey,
+    {
+        let registered = RegisteredType {
+            to_type: self.to_type,
+            default: self.default,
+            stable_key: Some(T::STABLE_KEY),
+        };
+        // The old key can't be dropped in a const fn, but a key that comes
+        // from a constant never owns an allocation, so forgetting it leaks
+        // nothing.
+        std::mem::forget(self);
+        registered
+    }
+
+    /// Returns the registered [`Type`].
+    pub fn to_type(&self) -> Type {
+        (self.to_type)()
+    }
+
+    /// Returns true if a default value of the type can be created.
+    pub fn has_default(&self) -> bool {
+        self.default.is_some()
+    }
+
+    /// Returns a default value of the type, if it was registered with one.
+    pub fn default_value(&self) -> Option<Box<dyn Any>> {
+        self.default.map(|default| default())
+    }
+
+    /// Returns the [`StableKey`] of the type, if it was registered with one.
+    pub fn stable_key(&self) -> Option<&StableKey> {
+        self.stable_key.as_ref()
+    }
+}
+
+fn boxed_default<T>() -> Box<dyn Any>
+where
+    T: Default + 'static,
+{
+    Box::new(T::default())
+}
+
+/// Returns an iterator over every registered type, in no particular order.
+pub fn all() -> impl Iterator<Item = &'static RegisteredType> {
+    inventory::iter::<RegisteredType>.into_iter()
+}
+
+/// Registers the [`StableKey`] of every registered type that has one, as if
+/// [`StableTypeKey::register()`] had been called for each of them.
+///
+/// Every key that doesn't conflict is registered. If any of them do, the
+/// error describes one of the conflicts.
+///
+/// The keys are also registered the first time any stable key is looked
+/// up, so calling this is only needed to find out about conflicts.
+pub fn register_stable_keys() -> Result<(), StableKeyConflict> {
+    let mut result = Ok(());
+
+    for registered in all() {
+        if let Some(key) = registered.stable_key() {
+            if let Err(conflict) = stable_key::register(key.clone(), registered.to_type()) {
+                result = Err(conflict);
+            }
+        }
+    }
+
+    result
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use inventory::submit;
+}
+
+/// Adds a type to the list of types in the [`registry`].
+///
+/// The type can be followed by `default`, if it implements [`Default`] and
+/// [`SingletonSet::populate_defaults()`] should insert it, and by
+/// `stable_key`, if it implements [`StableTypeKey`]. A stable key given this
+/// way is registered by the first lookup of any stable key, without a call
+/// to [`StableTypeKey::register()`].
+///
+/// ```
+/// use singletonset::{register_singleton, stable_type_key};
+///
+/// #[derive(Default)]
+/// struct Counter(u64);
+/// stable_type_key!(Counter = "app.counter", 1);
+///
+/// register_singleton!(Counter, default, stable_key);
+/// ```
+///
+/// [`registry`]: crate::registry
+/// [`SingletonSet::populate_defaults()`]: crate::SingletonSet::populate_defaults()
+/// [`StableTypeKey`]: crate::StableTypeKey
+/// [`StableTypeKey::register()`]: crate::StableTypeKey::register()
+#[macro_export]
+macro_rules! register_singleton {
+    ($t:ty $(, $option:ident)* $(,)?) => {
+        $crate::registry::__private::submit! {
+            $crate::register_singleton!(
+                @options $t; $crate::registry::RegisteredType::of::<$t>(); $($option)*
+            )
+        }
+    };
+    (@options $t:ty; $registered:expr;) => {
+        $registered
+    };
+    (@options $t:ty; $registered:expr; default $($rest:ident)*) => {
+        $crate::register_singleton!(@options $t; $registered.with_default::<$t>(); $($rest)*)
+    };
+    (@options $t:ty; $registered:expr; stable_key $($rest:ident)*) => {
+        $crate::register_singleton!(@options $t; $registered.with_stable_key::<$t>(); $($rest)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{stable_type_key, SingletonSet};
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct Defaulted(u8);
+
+    struct Keyed;
+    stable_type_key!(Keyed = "tests.registry.keyed", 1);
+
+    struct Plain;
+
+    struct LazilyKeyed;
+    stable_type_key!(LazilyKeyed = "tests.registry.lazily_keyed", 1);
+
+    register_singleton!(Defaulted, default);
+    register_singleton!(Keyed, stable_key);
+    register_singleton!(Plain);
+    register_singleton!(LazilyKeyed, stable_key);
+
+    fn find<T: 'static>() -> &'static RegisteredType {
+        all().find(|r| r.to_type() == Type::of::<T>()).unwrap()
+    }
+
+    #[test]
+    fn registered_types_keep_their_metadata() {
+        assert!(find::<Defaulted>().has_default());
+        assert!(!find::<Keyed>().has_default());
+        assert_eq!(
+            find::<Keyed>().stable_key(),
+            Some(&StableKey::named("tests.registry.keyed", 1))
+        );
+        assert_eq!(find::<Plain>().stable_key(), None);
+
+        register_stable_keys().unwrap();
+        assert_eq!(
+            Type::of::<Keyed>().stable_key(),
+            Some(StableKey::named("tests.registry.keyed", 1))
+        );
+    }
+
+    #[test]
+    fn stable_keys_are_registered_on_first_lookup() {
+        let key = StableKey::named("tests.registry.lazily_keyed", 1);
+        assert_eq!(key.registered_type(), Some(Type::of::<LazilyKeyed>()));
+        assert_eq!(Type::of::<LazilyKeyed>().stable_key(), Some(key));
+    }
+
+    #[test]
+    fn populate_defaults_keeps_existing_values() {
+        let mut set = SingletonSet::new();
+        set.insert(Defaulted(7));
+        set.populate_defaults();
+        assert_eq!(set.get::<Defaulted>(), &Defaulted(7));
+
+        let mut set = SingletonSet::new();
+        set.populate_defaults();
+        assert_eq!(set.get::<Defaulted>(), &Defaulted(0));
+        assert!(!set.contains::<Keyed>());
+        assert!(!set.contains::<Plain>());
+    }
+
+    #[test]
+    fn populate_defaults_skips_rejected_defaults() {
+        let mut set = SingletonSet::new();
+        set.register_validator(|value: &Defaulted| match value.0 {
+            0 => Err("zero"),
+            _ => Ok(()),
+        });
+
+        set.populate_defaults();
+        assert!(!set.contains::<Defaulted>());
+        assert_eq!(set.validation_report().len(), 1);
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use indexmap::IndexMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{JournaledSingletonSet, Sealed, SingletonSet, Type, TypeMismatch};
+
+type SerializeFn = fn(&dyn Any) -> Result<serde_json::Value, serde_json::Error>;
+type DeserializeFn = fn(serde_json::Value) -> Result<Box<dyn Any>, serde_json::Error>;
+
+#[derive(Debug)]
+struct Registration {
+    name: String,
+    serialize: SerializeFn,
+    deserialize: DeserializeFn,
+}
+
+fn serialize<T>(value: &dyn Any) -> Result<serde_json::Value, serde_json::Error>
+where
+    T: Serialize + 'static,
+{
+    match value.downcast_ref::<T>() {
+        Some(value) => serde_json::to_value(value),
+        None => Err(serde::ser::Error::custom(format_args!(
+            "value is not a {}",
+            Type::of::<T>()
+        ))),
+    }
+}
+
+fn deserialize<T>(value: serde_json::Value) -> Result<Box<dyn Any>, serde_json::Error>
+where
+    T: DeserializeOwned + 'static,
+{
+    serde_json::from_value::<T>(value).map(|value| Box::new(value) as Box<dyn Any>)
+}
+
+/// One mutation of a [`SingletonSet`], exported from the journal of a
+/// [`JournaledSingletonSet`] by [`SerdeRegistry::export()`].
+///
+/// Types are identified by the names they were registered with, and
+/// values are stored as JSON values, so a list of mutations can be saved
+/// in any format supported by serde and replayed in another program.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Mutation {
+    /// A value was inserted into a slot, replacing any value it held.
+    Insert {
+        /// The registered name of the value's type.
+        type_name: String,
+        /// The serialized value.
+        value: serde_json::Value,
+    },
+    /// The value was removed from a slot.
+    Remove {
+        /// The registered name of the slot's type.
+        type_name: String,
+    },
+}
+
+/// A list of types that can be serialized and deserialized, each with a
+/// unique name, used to export the journal of a [`JournaledSingletonSet`]
+/// and to replay it into another set.
+///
+/// This type is only available with the `replay` feature.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::{SerdeRegistry, SingletonSet};
+///
+/// let mut registry = SerdeRegistry::new();
+/// registry.register::<u8>("count");
+/// registry.register::<String>("title");
+///
+/// let mut set = SingletonSet::new().journaled(100);
+/// set.insert(1u8);
+/// set.insert("Draft".to_string());
+/// set.insert(2u8);
+/// set.remove::<String>();
+///
+/// let mutations = registry.export(&set).unwrap();
+/// let json = serde_json::to_string(&mutations).unwrap();
+///
+/// let mut replayed = SingletonSet::new();
+/// registry
+///     .replay(&serde_json::from_str::<Vec<_>>(&json).unwrap(), &mut replayed)
+///     .unwrap();
+/// assert_eq!(replayed.get::<u8>(), &2);
+/// assert!(!replayed.contains::<String>());
+/// ```
+#[derive(Debug, Default)]
+pub struct SerdeRegistry(IndexMap<Type, Registration>);
+
+impl SerdeRegistry {
+    /// Creates an empty `SerdeRegistry`.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        SerdeRegistry(IndexMap::new())
+    }
+
+    /// Registers the type `T` under the given name, replacing any earlier
+    /// registration of `T`.
+    ///
+    /// Names should be unique. If more than one type has the same name,
+    /// mutations that use the name are replayed as the type that was
+    /// registered first.
+    pub fn register<T>(&mut self, name: impl Into<String>)
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        self.0.insert(
+            Type::of::<T>(),
+            Registration {
+                name: name.into(),
+                serialize: serialize::<T>,
+                deserialize: deserialize::<T>,
+            },
+        );
+    }
+
+    /// Returns the name the [`Type`] was registered with.
+    pub fn name_of(&self, t: &Type) -> Option<&str> {
+        self.0.get(t).map(|registration| registration.name.as_str())
+    }
+
+    /// Returns the [`Type`] registered with the given name.
+    pub fn type_named(&self, name: &str) -> Option<Type> {
+        self.0
+            .iter()
+            .find(|(_, registration)| registration.name == name)
+            .map(|(t, _)| *t)
+    }
+
+    fn registration(&self, t: &Type) -> Result<&Registration, ReplayError> {
+        self.0.get(t).ok_or(ReplayError::Unregistered(*t))
+    }
+
+    /// Exports every change in the journal of `set` that can be undone, from
+    /// oldest to newest, as a list of [`Mutation`]s.
+    ///
+    /// Replaying the mutations into a set that looks like `set` did before
+    /// the oldest of them makes it look like `set` does now. Changes that
+    /// have fallen out of the journal, or were undone, are not exported.
+    pub fn export(&self, set: &JournaledSingletonSet) -> Result<Vec<Mutation>, ReplayError> {
+        set.applied()
+            .map(|(t, value)| {
+                let registration = self.registration(&t)?;
+                let type_name = registration.name.clone();
+
+                Ok(match value {
+                    Some(value) => Mutation::Insert {
+                        type_name,
+                        value: (registration.serialize)(value).map_err(ReplayError::Serde)?,
+                    },
+                    None => Mutation::Remove { type_name },
+                })
+            })
+            .collect()
+    }
+
+    /// Applies each [`Mutation`] to `set`, in order.
+    ///
+    /// If a mutation can't be applied, such as one that changes a sealed
+    /// slot, the error describes it, and the mutations before it are left
+    /// applied.
+    pub fn replay(
+        &self,
+        mutations: &[Mutation],
+        set: &mut SingletonSet,
+    ) -> Result<(), ReplayError> {
+        for mutation in mutations {
+            match mutation {
+                Mutation::Insert { type_name, value } => {
+                    let t = self
+                        .type_named(type_name)
+                        .ok_or_else(|| ReplayError::UnknownName(type_name.clone()))?;
+                    let value = (self.registration(&t)?.deserialize)(value.clone())
+                        .map_err(ReplayError::Serde)?;
+
+                    set.1.check_unsealed(&t).map_err(ReplayError::Sealed)?;
+                    set.insert_boxed(t, value).map_err(ReplayError::Mismatch)?;
+                }
+                Mutation::Remove { type_name } => {
+                    let t = self
+                        .type_named(type_name)
+                        .ok_or_else(|| ReplayError::UnknownName(type_name.clone()))?;
+
+                    set.1.check_unsealed(&t).map_err(ReplayError::Sealed)?;
+                    set.swap_slot(t, None);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The error returned when a journal can't be exported or replayed by a
+/// [`SerdeRegistry`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReplayError {
+    /// A change in the journal is to a type that isn't registered.
+    Unregistered(Type),
+    /// A mutation names a type that isn't registered.
+    UnknownName(String),
+    /// A value couldn't be serialized or deserialized.
+    Serde(serde_json::Error),
+    /// A value was deserialized as a different type than it was
+    /// registered as.
+    Mismatch(TypeMismatch),
+    /// A mutation changes a slot that is sealed.
+    Sealed(Sealed),
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Unregistered(t) => write!(f, "type {} is not registered", t),
+            ReplayError::UnknownName(name) => write!(f, "no type is registered as `{}`", name),
+            ReplayError::Serde(err) => write!(f, "{}", err),
+            ReplayError::Mismatch(err) => write!(f, "{}", err),
+            ReplayError::Sealed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Serde(err) => Some(err),
+            ReplayError::Mismatch(err) => Some(err),
+            ReplayError::Sealed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Cursor {
+        line: u32,
+    }
+
+    #[test]
+    fn replay_reproduces_the_exported_changes() {
+        let mut registry = SerdeRegistry::new();
+        registry.register::<Cursor>("cursor");
+        registry.register::<Vec<String>>("lines");
+
+        let mut set = SingletonSet::new().journaled(10);
+        set.insert(vec!["a".to_string()]);
+        set.insert(Cursor { line: 1 });
+        set.insert(vec!["a".to_string(), "b".to_string()]);
+        set.insert(Cursor { line: 2 });
+        set.insert(Cursor { line: 3 });
+        set.undo();
+
+        let mutations = registry.export(&set).unwrap();
+        assert_eq!(mutations.len(), 4);
+        assert_eq!(
+            mutations[3],
+            Mutation::Insert {
+                type_name: "cursor".to_string(),
+                value: serde_json::json!({ "line": 2 }),
+            }
+        );
+
+        let mut replayed = SingletonSet::new();
+        registry.replay(&mutations, &mut replayed).unwrap();
+        assert_eq!(replayed.get::<Cursor>(), &Cursor { line: 2 });
+        assert_eq!(replayed.get::<Vec<String>>().len(), 2);
+        assert!(replayed.types().eq(set.types()));
+    }
+
+    #[test]
+    fn unregistered_types_are_errors() {
+        let registry = SerdeRegistry::new();
+
+        let mut set = SingletonSet::new().journaled(10);
+        set.insert(1u8);
+        let err = registry.export(&set).unwrap_err();
+        assert_eq!(err.to_string(), "type u8 is not registered");
+
+        let mutations = [Mutation::Remove {
+            type_name: "missing".to_string(),
+        }];
+        let err = registry
+            .replay(&mutations, &mut SingletonSet::new())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no type is registered as `missing`");
+    }
+
+    #[test]
+    fn sealed_slots_stop_the_replay() {
+        let mut registry = SerdeRegistry::new();
+        registry.register::<Cursor>("cursor");
+        registry.register::<u8>("count");
+
+        let mutations = [
+            Mutation::Insert {
+                type_name: "count".to_string(),
+                value: serde_json::json!(1),
+            },
+            Mutation::Remove {
+                type_name: "cursor".to_string(),
+            },
+        ];
+
+        let mut set = SingletonSet::new();
+        set.insert(Cursor { line: 1 });
+        let _token = set.seal::<Cursor>().unwrap();
+
+        let err = registry.replay(&mutations, &mut set).unwrap_err();
+        assert!(
+            matches!(err, ReplayError::Sealed(sealed) if sealed.to_type() == Type::of::<Cursor>())
+        );
+        assert_eq!(set.get::<u8>(), &1);
+        assert_eq!(set.get::<Cursor>(), &Cursor { line: 1 });
+    }
+}
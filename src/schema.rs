@@ -0,0 +1,343 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+};
+
+use indexmap::IndexMap;
+
+use crate::{Bundle, SingletonSet, Type, TypeSet};
+
+/// One type described by a [`Schema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaEntry {
+    t: Type,
+    required: bool,
+    description: String,
+}
+
+impl SchemaEntry {
+    /// Returns the [`Type`] the entry describes.
+    pub fn to_type(&self) -> Type {
+        self.t
+    }
+
+    /// Returns true if a set must hold a value of the type to match the
+    /// schema.
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    /// Returns the description the entry was declared with.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl Display for SchemaEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.description.is_empty() {
+            true => write!(f, "{}", self.t),
+            false => write!(f, "{}: {}", self.t, self.description),
+        }
+    }
+}
+
+/// A declaration of the types a [`SingletonSet`] is expected to hold.
+///
+/// Each type in a schema is either required or optional, and has a
+/// description that is included in the [`SchemaReport`] returned by
+/// [`.validate()`]. A strict schema also reports every type the set holds
+/// that the schema doesn't mention.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::{Schema, SingletonSet};
+///
+/// struct Database;
+/// struct Cache;
+/// struct Config;
+///
+/// let schema = Schema::new()
+///     .require::<Config>("the parsed configuration file")
+///     .require_bundle::<(Database, Cache)>("a connection to a backing service")
+///     .optional::<u64>("the startup time")
+///     .strict(true);
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(Config);
+/// set.insert(Cache);
+/// set.insert("stray");
+///
+/// let report = schema.validate(&set);
+/// assert!(!report.is_valid());
+/// assert_eq!(report.missing().len(), 1);
+/// assert!(report.unexpected().contains::<&str>());
+/// assert!(report.to_string().contains("Database: a connection to a backing service"));
+/// ```
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`.validate()`]: Self::validate()
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    entries: IndexMap<Type, SchemaEntry>,
+    strict: bool,
+}
+
+impl Schema {
+    /// Creates an empty, lenient `Schema`.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Declares that the type is required, replacing any earlier entry for
+    /// it.
+    #[must_use]
+    pub fn require<T>(self, description: impl Into<String>) -> Self
+    where
+        T: 'static,
+    {
+        self.require_type(Type::of::<T>(), description)
+    }
+
+    /// Declares that the type is optional, replacing any earlier entry for
+    /// it.
+    #[must_use]
+    pub fn optional<T>(self, description: impl Into<String>) -> Self
+    where
+        T: 'static,
+    {
+        self.optional_type(Type::of::<T>(), description)
+    }
+
+    /// Declares that every type in the [`Bundle`] is required, with the same
+    /// description.
+    #[must_use]
+    pub fn require_bundle<B>(self, description: impl Into<String>) -> Self
+    where
+        B: Bundle,
+    {
+        self.bundle(B::types(), true, description.into())
+    }
+
+    /// Declares that every type in the [`Bundle`] is optional, with the same
+    /// description.
+    #[must_use]
+    pub fn optional_bundle<B>(self, description: impl Into<String>) -> Self
+    where
+        B: Bundle,
+    {
+        self.bundle(B::types(), false, description.into())
+    }
+
+    /// Declares that the given [`Type`] is required, replacing any earlier
+    /// entry for it.
+    #[must_use]
+    pub fn require_type(self, t: Type, description: impl Into<String>) -> Self {
+        self.entry(t, true, description.into())
+    }
+
+    /// Declares that the given [`Type`] is optional, replacing any earlier
+    /// entry for it.
+    #[must_use]
+    pub fn optional_type(self, t: Type, description: impl Into<String>) -> Self {
+        self.entry(t, false, description.into())
+    }
+
+    fn bundle(self, types: TypeSet, required: bool, description: String) -> Self {
+        types.into_iter().fold(self, |schema, t| {
+            schema.entry(t, required, description.clone())
+        })
+    }
+
+    fn entry(mut self, t: Type, required: bool, description: String) -> Self {
+        self.entries.insert(
+            t,
+            SchemaEntry {
+                t,
+                required,
+                description,
+            },
+        );
+        self
+    }
+
+    /// Sets whether types the schema doesn't mention make a set invalid.
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Returns true if types the schema doesn't mention make a set invalid.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Returns the entry for the given [`Type`], if the schema mentions it.
+    pub fn get(&self, t: &Type) -> Option<&SchemaEntry> {
+        self.entries.get(t)
+    }
+
+    /// Returns an iterator over every entry in the schema, in the order
+    /// they were declared.
+    pub fn entries(&self) -> impl Iterator<Item = &SchemaEntry> {
+        self.entries.values()
+    }
+
+    /// Checks `set` against the schema, and returns a report of every
+    /// required type it is missing and, if the schema is strict, every type
+    /// it holds that the schema doesn't mention.
+    pub fn validate(&self, set: &SingletonSet) -> SchemaReport {
+        let missing = self
+            .entries()
+            .filter(|entry| entry.required && !set.contains_type(&entry.t))
+            .cloned()
+            .collect();
+
+        let unexpected = match self.strict {
+            true => set
+                .types()
+                .filter(|t| !self.entries.contains_key(*t))
+                .collect(),
+            false => TypeSet::new(),
+        };
+
+        SchemaReport {
+            missing,
+            unexpected,
+        }
+    }
+}
+
+/// The result of checking a [`SingletonSet`] against a [`Schema`], created
+/// with [`Schema::validate()`].
+///
+/// The report is also an [`Error`], so an invalid set can be turned into an
+/// error with [`.into_result()`].
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`.into_result()`]: Self::into_result()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaReport {
+    missing: Vec<SchemaEntry>,
+    unexpected: TypeSet,
+}
+
+impl SchemaReport {
+    /// Returns the entry of every required type the set is missing, in the
+    /// order they were declared.
+    pub fn missing(&self) -> &[SchemaEntry] {
+        &self.missing
+    }
+
+    /// Returns every type the set holds that a strict schema doesn't
+    /// mention, in the order of the set.
+    pub fn unexpected(&self) -> &TypeSet {
+        &self.unexpected
+    }
+
+    /// Returns true if the set matches the schema.
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+
+    /// Returns `Ok(())` if the set matches the schema, or the report as an
+    /// error otherwise.
+    pub fn into_result(self) -> Result<(), SchemaReport> {
+        match self.is_valid() {
+            true => Ok(()),
+            false => Err(self),
+        }
+    }
+}
+
+impl Display for SchemaReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_valid() {
+            return write!(f, "the set matches the schema");
+        }
+
+        if !self.missing.is_empty() {
+            write!(f, "missing required types:")?;
+            for entry in &self.missing {
+                write!(f, "\n  - {}", entry)?;
+            }
+        }
+
+        if !self.unexpected.is_empty() {
+            if !self.missing.is_empty() {
+                writeln!(f)?;
+            }
+            write!(f, "unexpected types:")?;
+            for t in &self.unexpected {
+                write!(f, "\n  - {}", t)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for SchemaReport {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lenient_schemas_only_check_required_types() {
+        let schema = Schema::new()
+            .require_bundle::<(u8, u16)>("counters")
+            .optional::<u32>("")
+            .require::<u16>("the port");
+
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert("extra");
+
+        let report = schema.validate(&set);
+        assert_eq!(report.missing().len(), 1);
+        assert_eq!(report.missing()[0].to_type(), Type::of::<u16>());
+        assert_eq!(report.missing()[0].description(), "the port");
+        assert!(report.unexpected().is_empty());
+        assert_eq!(
+            report.to_string(),
+            "missing required types:\n  - u16: the port"
+        );
+
+        set.insert(2u16);
+        assert_eq!(schema.validate(&set).into_result(), Ok(()));
+        assert!(!schema.get(&Type::of::<u32>()).unwrap().is_required());
+    }
+
+    #[test]
+    fn strict_schemas_report_unexpected_types() {
+        let schema = Schema::new()
+            .require::<u8>("")
+            .optional_type(Type::of::<u32>(), "an id")
+            .strict(true);
+
+        let mut set = SingletonSet::new();
+        set.insert(3u32);
+        set.insert(4u64);
+        set.insert(false);
+
+        let report = schema.validate(&set);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.to_string(),
+            "missing required types:\n  - u8\nunexpected types:\n  - u64\n  - bool"
+        );
+        assert_eq!(
+            schema.validate(&SingletonSet::new()).to_string(),
+            "missing required types:\n  - u8"
+        );
+        assert_eq!(
+            Schema::new().validate(&set).to_string(),
+            "the set matches the schema"
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::Type;
+
+/// The source of the id of each [`UnsealToken`].
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+/// The capability to unseal the slots that were sealed along with it.
+///
+/// A token is returned by [`SingletonSet::seal()`] and
+/// [`SingletonSet::seal_all()`], and is the only way to unseal the slots
+/// they sealed, with [`SingletonSet::unseal()`] or
+/// [`SingletonSet::unseal_all()`]. It can't be cloned or created any other
+/// way, so code without the token can't change a sealed slot through the
+/// set's methods. Dropping the token seals the slots for good.
+///
+/// Seals belong to the set, not to its values. They guard against mistakes
+/// rather than hostile code: whoever holds `&mut SingletonSet` can still
+/// replace the whole set, for instance with [`std::mem::take()`], and
+/// converting the set with [`SingletonSet::freeze()`] or into a
+/// [`MultitonSet`] drops its seals.
+///
+/// [`SingletonSet::seal()`]: crate::SingletonSet::seal()
+/// [`SingletonSet::seal_all()`]: crate::SingletonSet::seal_all()
+/// [`SingletonSet::unseal()`]: crate::SingletonSet::unseal()
+/// [`SingletonSet::unseal_all()`]: crate::SingletonSet::unseal_all()
+/// [`SingletonSet::freeze()`]: crate::SingletonSet::freeze()
+/// [`MultitonSet`]: crate::MultitonSet
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "the slots can only be unsealed with this token"]
+pub struct UnsealToken(u64);
+
+impl UnsealToken {
+    pub(crate) fn new() -> Self {
+        UnsealToken(NEXT_TOKEN.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub(crate) fn id(&self) -> u64 {
+        self.0
+    }
+}
+
+/// The error returned when a sealed slot of a [`SingletonSet`] would be
+/// changed.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sealed(pub(crate) Type);
+
+impl Sealed {
+    /// Returns the [`Type`] of the sealed slot.
+    pub fn to_type(&self) -> Type {
+        self.0
+    }
+}
+
+impl Display for Sealed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the slot of {} is sealed", self.0)
+    }
+}
+
+impl Error for Sealed {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SingletonSet, Type};
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Config(u8);
+
+    #[test]
+    fn sealed_slots_reject_changes() {
+        let mut set = SingletonSet::new();
+        set.insert(Config(1));
+        set.insert(2u8);
+
+        let token = set.seal::<Config>().unwrap();
+        assert!(set.is_sealed::<Config>());
+        assert!(set.seal::<Config>().is_none());
+        assert!(set.seal::<u16>().is_none());
+
+        assert!(set.try_as_mut::<Config>().is_none());
+        assert!(set.get_by_type_mut(&Type::of::<Config>()).is_none());
+        assert_eq!(set.iter_mut().count(), 1);
+
+        let err = set.checked_insert(Config(3)).unwrap_err();
+        assert_eq!(err.to_type(), Type::of::<Config>());
+        assert!(err.to_string().ends_with("::Config is sealed"));
+        assert_eq!(set.checked_remove::<Config>().unwrap_err(), err);
+        assert_eq!(set.checked_as_mut::<Config>().unwrap_err(), err);
+        assert_eq!(set.checked_with_mut(|c: &mut Config| c.0 += 1), Err(err));
+        assert_eq!(set.checked_clear(), Err(err));
+        assert_eq!(set.checked_remove::<u8>(), Ok(Some(2)));
+        assert_eq!(set.get::<Config>(), &Config(1));
+
+        let other = set.seal_all();
+        assert!(!set.unseal::<Config>(&other));
+        assert!(set.unseal::<Config>(&token));
+        assert!(!set.is_sealed::<Config>());
+        assert_eq!(set.checked_insert(Config(4)), Ok(Some(Config(1))));
+    }
+
+    #[test]
+    #[should_panic(expected = "::Config is sealed")]
+    fn the_panicking_api_panics_on_sealed_slots() {
+        let mut set = SingletonSet::new();
+        set.insert(Config(1));
+        set.insert(2u8);
+
+        let token = set.seal_all();
+        assert_eq!(set.unseal_all(&token), 2);
+        let _token = set.seal_all();
+
+        set.get_mut::<Config>().0 = 2;
+    }
+}
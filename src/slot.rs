@@ -0,0 +1,263 @@
+use std::{
+    any::Any,
+    fmt::{Debug, Formatter},
+    pin::Pin,
+    sync::Arc,
+};
+
+/// How the value in a slot of a [`SingletonSet`] is stored.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SlotKind {
+    /// The set owns the value, which was inserted by a method like
+    /// [`.insert()`].
+    ///
+    /// [`.insert()`]: crate::SingletonSet::insert()
+    Owned,
+    /// The set owns a boxed value of an unsized type, which was inserted
+    /// by [`.insert_unsized()`].
+    ///
+    /// [`.insert_unsized()`]: crate::SingletonSet::insert_unsized()
+    Unsized,
+    /// The set holds one of possibly many `Arc`s pointing to the value,
+    /// which was inserted by [`.insert_shared()`] or [`.insert_arc()`].
+    ///
+    /// [`.insert_shared()`]: crate::SingletonSet::insert_shared()
+    /// [`.insert_arc()`]: crate::SingletonSet::insert_arc()
+    Shared,
+    /// The set owns the value and has promised not to move it until it is
+    /// removed or the set is dropped. The value was inserted by
+    /// [`.insert_pinned()`].
+    ///
+    /// [`.insert_pinned()`]: crate::SingletonSet::insert_pinned()
+    Pinned,
+}
+
+/// What a slot needs to know to reach the value it stores.
+#[derive(Clone, Copy)]
+pub(crate) enum Storage {
+    Owned,
+    Unsized,
+    Shared {
+        get: fn(&dyn Any) -> &dyn Any,
+        get_mut: fn(&mut dyn Any) -> Option<&mut dyn Any>,
+        unique: fn(&dyn Any) -> bool,
+    },
+    Pinned {
+        get: fn(&dyn Any) -> &dyn Any,
+    },
+}
+
+impl Storage {
+    pub(crate) fn shared<T>() -> Self
+    where
+        T: 'static,
+    {
+        Storage::Shared {
+            get: shared_get::<T>,
+            get_mut: shared_get_mut::<T>,
+            unique: shared_unique::<T>,
+        }
+    }
+
+    pub(crate) fn pinned<T>() -> Self
+    where
+        T: 'static,
+    {
+        Storage::Pinned {
+            get: pinned_get::<T>,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> SlotKind {
+        match self {
+            Storage::Owned => SlotKind::Owned,
+            Storage::Unsized => SlotKind::Unsized,
+            Storage::Shared { .. } => SlotKind::Shared,
+            Storage::Pinned { .. } => SlotKind::Pinned,
+        }
+    }
+
+    /// Returns the value behind whatever `raw` is stored as.
+    pub(crate) fn get<'a>(&self, raw: &'a dyn Any) -> &'a dyn Any {
+        match self {
+            Storage::Owned | Storage::Unsized => raw,
+            Storage::Shared { get, .. } | Storage::Pinned { get } => get(raw),
+        }
+    }
+
+    /// Returns the value behind whatever `raw` is stored as, if it can be
+    /// borrowed mutably.
+    ///
+    /// A pinned value is never borrowed mutably, since a `&mut` reference
+    /// would allow it to be moved.
+    pub(crate) fn get_mut<'a>(&self, raw: &'a mut dyn Any) -> Option<&'a mut dyn Any> {
+        match self {
+            Storage::Owned | Storage::Unsized => Some(raw),
+            Storage::Shared { get_mut, .. } => get_mut(raw),
+            Storage::Pinned { .. } => None,
+        }
+    }
+}
+
+impl Debug for Storage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.kind(), f)
+    }
+}
+
+fn shared_get<T>(raw: &dyn Any) -> &dyn Any
+where
+    T: 'static,
+{
+    match raw.downcast_ref::<Arc<T>>() {
+        Some(arc) => &**arc,
+        None => raw,
+    }
+}
+
+fn shared_get_mut<T>(raw: &mut dyn Any) -> Option<&mut dyn Any>
+where
+    T: 'static,
+{
+    raw.downcast_mut::<Arc<T>>()
+        .and_then(Arc::get_mut)
+        .map(|value| value as &mut dyn Any)
+}
+
+fn shared_unique<T>(raw: &dyn Any) -> bool
+where
+    T: 'static,
+{
+    raw.downcast_ref::<Arc<T>>()
+        .map_or(false, |arc| Arc::strong_count(arc) == 1)
+}
+
+fn pinned_get<T>(raw: &dyn Any) -> &dyn Any
+where
+    T: 'static,
+{
+    match raw.downcast_ref::<Pin<Box<T>>>() {
+        Some(pinned) => &**pinned,
+        None => raw,
+    }
+}
+
+/// A value held by a [`SingletonSet`], along with how it is stored.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+pub(crate) struct Slot {
+    raw: Box<dyn Any>,
+    storage: Storage,
+}
+
+impl Slot {
+    pub(crate) fn new<T>(value: T) -> Self
+    where
+        T: 'static,
+    {
+        Slot::from_parts(Box::new(value), Storage::Owned)
+    }
+
+    pub(crate) fn from_parts(raw: Box<dyn Any>, storage: Storage) -> Self {
+        Slot { raw, storage }
+    }
+
+    pub(crate) fn into_parts(self) -> (Box<dyn Any>, Storage) {
+        (self.raw, self.storage)
+    }
+
+    pub(crate) fn kind(&self) -> SlotKind {
+        self.storage.kind()
+    }
+
+    /// Returns the value as it is stored, such as the `Arc` of a shared
+    /// slot.
+    pub(crate) fn raw(&self) -> &dyn Any {
+        &*self.raw
+    }
+
+    pub(crate) fn raw_mut(&mut self) -> &mut dyn Any {
+        &mut *self.raw
+    }
+
+    pub(crate) fn get(&self) -> &dyn Any {
+        self.storage.get(&*self.raw)
+    }
+
+    pub(crate) fn get_mut(&mut self) -> Option<&mut dyn Any> {
+        self.storage.get_mut(&mut *self.raw)
+    }
+
+    pub(crate) fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        self.get().downcast_ref()
+    }
+
+    pub(crate) fn downcast_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        self.get_mut().and_then(|value| value.downcast_mut())
+    }
+
+    /// Returns true if [`.into_inner::<T>()`] would return the value,
+    /// because the set owns it as a `T` and it isn't pinned.
+    ///
+    /// The boxed value of an unsized slot is taken as a `Box<U>`, so a value
+    /// of a sized type is never taken out of a slot of the other storage.
+    ///
+    /// [`.into_inner::<T>()`]: Self::into_inner()
+    pub(crate) fn can_take<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        match self.storage {
+            Storage::Owned | Storage::Unsized => self.raw.is::<T>(),
+            Storage::Shared { unique, .. } => self.raw.is::<Arc<T>>() && unique(&*self.raw),
+            Storage::Pinned { .. } => false,
+        }
+    }
+
+    /// Returns the owned value, or [`None`] if the value is shared and
+    /// other `Arc`s still point to it, or if it is pinned. A pinned value is
+    /// dropped in place instead.
+    pub(crate) fn into_inner<T>(self) -> Option<T>
+    where
+        T: 'static,
+    {
+        match self.storage {
+            Storage::Shared { .. } => self
+                .raw
+                .downcast::<Arc<T>>()
+                .ok()
+                .and_then(|arc| Arc::try_unwrap(*arc).ok()),
+            Storage::Pinned { .. } => None,
+            _ => self.raw.downcast().ok().map(|value| *value),
+        }
+    }
+
+    /// Returns the value as an `Arc`, wrapping an owned value in a new one.
+    pub(crate) fn into_arc<T>(self) -> Option<Arc<T>>
+    where
+        T: 'static,
+    {
+        match self.storage {
+            Storage::Shared { .. } => self.raw.downcast().ok().map(|arc| *arc),
+            Storage::Pinned { .. } => None,
+            _ => self.raw.downcast().ok().map(|value| Arc::new(*value)),
+        }
+    }
+}
+
+impl Debug for Slot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Slot")
+            .field("kind", &self.kind())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{Display, Formatter},
+    str::FromStr,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use crate::Type;
+
+/// Every stable key that has been registered, along with its type.
+static REGISTRY: Mutex<Vec<(StableKey, Type)>> = Mutex::new(Vec::new());
+
+/// Locks [`REGISTRY`] to look a key up, after registering the keys of the
+/// types registered with `register_singleton!` the first time it is called.
+fn registry() -> MutexGuard<'static, Vec<(StableKey, Type)>> {
+    #[cfg(feature = "inventory")]
+    {
+        static FROM_INVENTORY: std::sync::Once = std::sync::Once::new();
+        // Conflicts are left for `register_stable_keys()` to report.
+        FROM_INVENTORY.call_once(|| {
+            let _ = crate::registry::register_stable_keys();
+        });
+    }
+
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// An identifier for a type that stays the same across builds, so it can be
+/// stored on disk or sent to another process.
+///
+/// A key is either a name with a version number, or a 128-bit number, such
+/// as a UUID. Both are chosen by the author of the type. Keys with the same
+/// name but different versions are different keys, so a type can be given
+/// a new version when its representation changes.
+///
+/// A key can be written out with [`Display`] and read back with
+/// [`FromStr`]. Named keys are written as `name@version`, and numeric keys
+/// are written as 32 hexadecimal digits prefixed with `0x`. Since only
+/// named keys contain an `@`, a name may itself look like a number, such
+/// as `0xfeed`, and still be read back as a name. Only a key with an empty
+/// name can't be read back.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::StableKey;
+///
+/// let key = StableKey::named("app.config", 2);
+/// assert_eq!(key.to_string(), "app.config@2");
+/// assert_eq!("app.config@2".parse(), Ok(key));
+///
+/// let key = StableKey::id(0x6f1d);
+/// assert_eq!(key.to_string().parse(), Ok(key));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StableKey {
+    /// A name chosen for the type, along with a version number.
+    Named {
+        /// The name of the type.
+        name: Cow<'static, str>,
+        /// The version of the type's representation.
+        version: u32,
+    },
+    /// A 128-bit number chosen for the type.
+    Id(u128),
+}
+
+impl StableKey {
+    /// Creates a key from a name and a version number.
+    pub const fn named(name: &'static str, version: u32) -> Self {
+        StableKey::Named {
+            name: Cow::Borrowed(name),
+            version,
+        }
+    }
+
+    /// Creates a key from a 128-bit number.
+    pub const fn id(id: u128) -> Self {
+        StableKey::Id(id)
+    }
+
+    /// Returns the [`Type`] that registered this key, if any.
+    pub fn registered_type(&self) -> Option<Type> {
+        registry()
+            .iter()
+            .find(|(key, _)| key == self)
+            .map(|(_, t)| *t)
+    }
+}
+
+impl Display for StableKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StableKey::Named { name, version } => write!(f, "{}@{}", name, version),
+            StableKey::Id(id) => write!(f, "{:#034x}", id),
+        }
+    }
+}
+
+impl FromStr for StableKey {
+    type Err = ParseStableKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // A numeric key never contains an `@`, so anything that does is a
+        // named key, even if its name starts with `0x`.
+        let (name, version) = match s.rsplit_once('@') {
+            Some(parts) => parts,
+            None => {
+                let hex = s.strip_prefix("0x").ok_or(ParseStableKeyError(()))?;
+                return u128::from_str_radix(hex, 16)
+                    .map(StableKey::Id)
+                    .map_err(|_| ParseStableKeyError(()));
+            }
+        };
+        if name.is_empty() {
+            return Err(ParseStableKeyError(()));
+        }
+
+        Ok(StableKey::Named {
+            name: Cow::Owned(name.to_string()),
+            version: version.parse().map_err(|_| ParseStableKeyError(()))?,
+        })
+    }
+}
+
+/// The error returned when a string is not a valid [`StableKey`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseStableKeyError(());
+
+impl Display for ParseStableKeyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected `name@version` or a hexadecimal `0x` key")
+    }
+}
+
+impl Error for ParseStableKeyError {}
+
+/// A type with a [`StableKey`] chosen by its author.
+///
+/// This is usually implemented with the [`stable_type_key!`] macro. Before
+/// the key can be found from its [`Type`], the type must be registered with
+/// [`register()`], which also checks that no other type has already
+/// registered the same key.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::{stable_type_key, SingletonSet, StableKey, StableTypeKey, Type};
+///
+/// struct Config;
+/// stable_type_key!(Config = "app.config", 1);
+///
+/// Config::register().unwrap();
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(Config);
+///
+/// let key = StableKey::named("app.config", 1);
+/// assert_eq!(Type::of::<Config>().stable_key(), Some(key.clone()));
+/// assert_eq!(set.type_by_stable_key(&key), Some(Type::of::<Config>()));
+/// ```
+///
+/// [`register()`]: Self::register()
+/// [`stable_type_key!`]: crate::stable_type_key!
+pub trait StableTypeKey: 'static {
+    /// The key of the type.
+    const STABLE_KEY: StableKey;
+
+    /// Registers the key of the type, so it can be found from the
+    /// [`Type`], and the `Type` can be found from the key.
+    ///
+    /// Registering the same type more than once has no effect. If another
+    /// type has already registered the same key, nothing is registered and
+    /// the error names both types.
+    fn register() -> Result<(), StableKeyConflict>
+    where
+        Self: Sized,
+    {
+        register(Self::STABLE_KEY, Type::of::<Self>())
+    }
+}
+
+pub(crate) fn register(key: StableKey, t: Type) -> Result<(), StableKeyConflict> {
+    let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some((_, existing)) = registry.iter().find(|(k, _)| *k == key) {
+        return if *existing == t {
+            Ok(())
+        } else {
+            Err(StableKeyConflict(Box::new((key, *existing, t))))
+        };
+    }
+
+    registry.push((key, t));
+    Ok(())
+}
+
+pub(crate) fn stable_key_of(t: &Type) -> Option<StableKey> {
+    registry()
+        .iter()
+        .find(|(_, registered)| registered == t)
+        .map(|(key, _)| key.clone())
+}
+
+/// The error returned when two types register the same [`StableKey`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StableKeyConflict(Box<(StableKey, Type, Type)>);
+
+impl StableKeyConflict {
+    /// Returns the key both types tried to register.
+    pub fn key(&self) -> &StableKey {
+        &self.0 .0
+    }
+
+    /// Returns the type that registered the key first, and still holds it.
+    pub fn existing(&self) -> Type {
+        self.0 .1
+    }
+
+    /// Returns the type whose registration was rejected.
+    pub fn rejected(&self) -> Type {
+        self.0 .2
+    }
+}
+
+impl Display for StableKeyConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stable key `{}` of {} is already registered by {}",
+            self.0 .0, self.0 .2, self.0 .1
+        )
+    }
+}
+
+impl Error for StableKeyConflict {}
+
+/// Implements [`StableTypeKey`] for a type.
+///
+/// A named key is given as a string literal and a version number, and a
+/// numeric key as a single integer literal.
+///
+/// ```
+/// use singletonset::stable_type_key;
+///
+/// struct Config;
+/// struct Session;
+///
+/// stable_type_key!(Config = "app.config", 1);
+/// stable_type_key!(Session = 0x9a4c_2e1b_0f7d_4c39_8a41_5be2_d0c6_7f13);
+/// ```
+///
+/// [`StableTypeKey`]: crate::StableTypeKey
+#[macro_export]
+macro_rules! stable_type_key {
+    ($t:ty = $name:literal, $version:literal) => {
+        impl $crate::StableTypeKey for $t {
+            const STABLE_KEY: $crate::StableKey = $crate::StableKey::named($name, $version);
+        }
+    };
+    ($t:ty = $id:literal) => {
+        impl $crate::StableTypeKey for $t {
+            const STABLE_KEY: $crate::StableKey = $crate::StableKey::id($id);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct First;
+    struct Second;
+    struct Numbered;
+
+    stable_type_key!(First = "tests.conflict", 1);
+    stable_type_key!(Second = "tests.conflict", 1);
+    stable_type_key!(Numbered = 0xdead_beef);
+
+    #[test]
+    fn conflicting_keys_are_rejected() {
+        First::register().unwrap();
+        First::register().unwrap();
+
+        let err = Second::register().unwrap_err();
+
+        assert_eq!(err.key(), &StableKey::named("tests.conflict", 1));
+        assert_eq!(err.existing(), Type::of::<First>());
+        assert_eq!(err.rejected(), Type::of::<Second>());
+        assert_eq!(Type::of::<Second>().stable_key(), None);
+        assert_eq!(
+            StableKey::named("tests.conflict", 1).registered_type(),
+            Some(Type::of::<First>())
+        );
+    }
+
+    #[test]
+    fn keys_round_trip_through_strings() {
+        Numbered::register().unwrap();
+
+        let key = Type::of::<Numbered>().stable_key().unwrap();
+        assert_eq!(key.to_string(), "0x000000000000000000000000deadbeef");
+
+        let parsed: StableKey = key.to_string().parse().unwrap();
+        assert_eq!(parsed.registered_type(), Some(Type::of::<Numbered>()));
+
+        let parsed: StableKey = "a@b@3".parse().unwrap();
+        assert_eq!(parsed, StableKey::named("a@b", 3));
+
+        let key = StableKey::named("0xfoo", 1);
+        assert_eq!(key.to_string(), "0xfoo@1");
+        assert_eq!(key.to_string().parse(), Ok(key));
+        assert_eq!("0x1@2".parse(), Ok(StableKey::named("0x1", 2)));
+
+        assert!("no-version".parse::<StableKey>().is_err());
+        assert!("@1".parse::<StableKey>().is_err());
+        assert!("0xzz".parse::<StableKey>().is_err());
+    }
+}
@@ -0,0 +1,414 @@
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Formatter, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use indexmap::IndexMap;
+
+use crate::Type;
+
+/// A source of the current time for the statistics of a [`SingletonSet`].
+///
+/// The set uses [`SystemClock`] unless another clock is given to
+/// [`SingletonSet::set_clock()`]. Any `Fn() -> SystemTime` closure is also
+/// a clock, which makes it easy to control time in tests.
+///
+/// This trait is only available with the `stats` feature.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::set_clock()`]: crate::SingletonSet::set_clock()
+pub trait Clock: 'static {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> SystemTime + 'static,
+{
+    fn now(&self) -> SystemTime {
+        self()
+    }
+}
+
+/// The [`Clock`] that reads the time from [`SystemTime::now()`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// The access statistics of one slot of a [`SingletonSet`].
+///
+/// Statistics belong to the slot rather than to its value. They start when
+/// a value is inserted into the empty slot and end when the slot is
+/// emptied, and replacing the value counts as a write.
+///
+/// This type is only available with the `stats` feature.
+///
+/// [`SingletonSet`]: crate::SingletonSet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotStats {
+    reads: u64,
+    writes: u64,
+    inserted_at: SystemTime,
+    last_access: SystemTime,
+}
+
+impl SlotStats {
+    fn new(now: SystemTime) -> Self {
+        SlotStats {
+            reads: 0,
+            writes: 0,
+            inserted_at: now,
+            last_access: now,
+        }
+    }
+
+    /// Returns the number of times the value was borrowed immutably.
+    pub fn reads(&self) -> u64 {
+        self.reads
+    }
+
+    /// Returns the number of times the value was borrowed mutably or
+    /// replaced.
+    pub fn writes(&self) -> u64 {
+        self.writes
+    }
+
+    /// Returns the time a value was inserted into the empty slot.
+    pub fn inserted_at(&self) -> SystemTime {
+        self.inserted_at
+    }
+
+    /// Returns the time the slot was last read, written or filled.
+    pub fn last_access(&self) -> SystemTime {
+        self.last_access
+    }
+}
+
+/// The statistics of every slot of a [`SingletonSet`], created with
+/// [`SingletonSet::stats()`].
+///
+/// The report can be rendered in the Prometheus text exposition format
+/// with [`.to_prometheus()`].
+///
+/// This type is only available with the `stats` feature.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use singletonset::SingletonSet;
+///
+/// struct Config;
+///
+/// let mut set = SingletonSet::new();
+/// set.set_clock(|| UNIX_EPOCH + Duration::from_secs(60));
+/// set.insert(Config);
+/// set.get::<Config>();
+///
+/// let stats = set.stats_for::<Config>().unwrap();
+/// assert_eq!(stats.reads(), 1);
+/// assert_eq!(stats.writes(), 0);
+///
+/// let text = set.stats().to_prometheus();
+/// assert!(text.contains("{type=\"Config\",type_name=\""));
+/// assert!(text.contains("::Config\"} 1\n"));
+/// ```
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::stats()`]: crate::SingletonSet::stats()
+/// [`.to_prometheus()`]: Self::to_prometheus()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatsReport {
+    slots: Vec<(Type, SlotStats)>,
+}
+
+impl StatsReport {
+    /// Returns the [`Type`] and statistics of each slot, in the order of the
+    /// set.
+    pub fn slots(&self) -> &[(Type, SlotStats)] {
+        &self.slots
+    }
+
+    /// Returns the statistics of the slot of the given [`Type`].
+    pub fn get(&self, t: &Type) -> Option<&SlotStats> {
+        self.slots
+            .iter()
+            .find(|(slot, _)| slot == t)
+            .map(|(_, stats)| stats)
+    }
+
+    /// Renders the statistics in the Prometheus text exposition format.
+    ///
+    /// Each slot is labelled with the short name of its type, from
+    /// [`Type::as_name()`], as `type`, and with its full name, from
+    /// [`Type::as_str()`], as `type_name`. Short names like `Vec` may be
+    /// shared by several slots, which the full name tells apart. Times are
+    /// given in seconds since the Unix epoch.
+    pub fn to_prometheus(&self) -> String {
+        type Metric = (
+            &'static str,
+            &'static str,
+            &'static str,
+            fn(&SlotStats) -> String,
+        );
+
+        let metrics: [Metric; 4] = [
+            (
+                "singletonset_slot_reads_total",
+                "counter",
+                "Number of times the slot's value was borrowed immutably.",
+                |stats| stats.reads.to_string(),
+            ),
+            (
+                "singletonset_slot_writes_total",
+                "counter",
+                "Number of times the slot's value was borrowed mutably or replaced.",
+                |stats| stats.writes.to_string(),
+            ),
+            (
+                "singletonset_slot_inserted_timestamp_seconds",
+                "gauge",
+                "Time a value was inserted into the empty slot.",
+                |stats| seconds(stats.inserted_at),
+            ),
+            (
+                "singletonset_slot_last_access_timestamp_seconds",
+                "gauge",
+                "Time the slot was last read, written or filled.",
+                |stats| seconds(stats.last_access),
+            ),
+        ];
+
+        let mut out = String::new();
+        for (name, kind, help, value) in metrics {
+            // Writing to a `String` never fails.
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+
+            for (t, stats) in &self.slots {
+                let _ = writeln!(
+                    out,
+                    "{}{{type=\"{}\",type_name=\"{}\"}} {}",
+                    name,
+                    escape_label(t.as_name()),
+                    escape_label(t.as_str()),
+                    value(stats)
+                );
+            }
+        }
+
+        out
+    }
+}
+
+fn seconds(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+        .to_string()
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// The statistics of every slot of a set, along with the clock they are
+/// taken from.
+///
+/// Reads are recorded through a shared borrow of the set, so the table is
+/// kept in a [`RefCell`].
+pub(crate) struct Stats {
+    clock: Box<dyn Clock>,
+    slots: RefCell<IndexMap<Type, SlotStats>>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            clock: Box::new(SystemClock),
+            slots: RefCell::default(),
+        }
+    }
+}
+
+impl Stats {
+    pub(crate) fn set_clock(&mut self, clock: impl Clock) {
+        self.clock = Box::new(clock);
+    }
+
+    pub(crate) fn read(&self, t: &Type) {
+        if let Some(stats) = self.slots.borrow_mut().get_mut(t) {
+            stats.reads += 1;
+            stats.last_access = self.clock.now();
+        }
+    }
+
+    pub(crate) fn written(&mut self, t: &Type) {
+        if let Some(stats) = self.slots.get_mut().get_mut(t) {
+            stats.writes += 1;
+            stats.last_access = self.clock.now();
+        }
+    }
+
+    pub(crate) fn written_all(&mut self) {
+        let now = self.clock.now();
+        for stats in self.slots.get_mut().values_mut() {
+            stats.writes += 1;
+            stats.last_access = now;
+        }
+    }
+
+    pub(crate) fn inserted(&mut self, t: &Type) {
+        let now = self.clock.now();
+        self.slots
+            .get_mut()
+            .entry(*t)
+            .and_modify(|stats| {
+                stats.writes += 1;
+                stats.last_access = now;
+            })
+            .or_insert_with(|| SlotStats::new(now));
+    }
+
+    pub(crate) fn removed(&mut self, t: &Type) {
+        self.slots.get_mut().shift_remove(t);
+    }
+
+    pub(crate) fn removed_all(&mut self) {
+        self.slots.get_mut().clear();
+    }
+
+    pub(crate) fn get(&self, t: &Type) -> Option<SlotStats> {
+        self.slots.borrow().get(t).copied()
+    }
+
+    /// Returns the statistics of each of the given types that has them.
+    pub(crate) fn report<'a>(&self, types: impl Iterator<Item = &'a Type>) -> StatsReport {
+        let slots = self.slots.borrow();
+
+        StatsReport {
+            slots: types
+                .filter_map(|t| slots.get(t).map(|stats| (*t, *stats)))
+                .collect(),
+        }
+    }
+}
+
+impl Debug for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Stats").field(&self.slots).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use super::*;
+    use crate::SingletonSet;
+
+    #[test]
+    fn reads_and_writes_are_counted_with_the_injected_clock() {
+        let seconds = Rc::new(Cell::new(10));
+        let clock = Rc::clone(&seconds);
+
+        let mut set = SingletonSet::new();
+        set.set_clock(move || UNIX_EPOCH + Duration::from_secs(clock.get()));
+        set.insert(1u8);
+        set.insert(vec![0u16]);
+
+        seconds.set(20);
+        set.get::<u8>();
+        set.try_get::<u8>();
+        set.try_get_mut::<Vec<u16>>().unwrap().push(1);
+        set.insert(vec![2u16]);
+        let _ = set.try_get::<u32>();
+
+        let stats = set.stats_for::<u8>().unwrap();
+        assert_eq!((stats.reads(), stats.writes()), (2, 0));
+        assert_eq!(stats.inserted_at(), UNIX_EPOCH + Duration::from_secs(10));
+        assert_eq!(stats.last_access(), UNIX_EPOCH + Duration::from_secs(20));
+
+        let stats = set.stats_for::<Vec<u16>>().unwrap();
+        assert_eq!((stats.reads(), stats.writes()), (0, 2));
+        assert_eq!(stats.inserted_at(), UNIX_EPOCH + Duration::from_secs(10));
+
+        set.remove::<u8>();
+        assert_eq!(set.stats_for::<u8>(), None);
+        set.insert(3u8);
+        assert_eq!(set.stats_for::<u8>().unwrap().reads(), 0);
+        assert_eq!(set.stats().slots().len(), 2);
+    }
+
+    #[test]
+    fn prometheus_output_labels_slots_by_name() {
+        let mut set = SingletonSet::new();
+        set.set_clock(|| UNIX_EPOCH + Duration::from_millis(1500));
+        set.insert(7u32);
+        set.get::<u32>();
+
+        let text = set.stats().to_prometheus();
+        assert!(text.starts_with(
+            "# HELP singletonset_slot_reads_total Number of times the slot's value was borrowed immutably.\n\
+             # TYPE singletonset_slot_reads_total counter\n\
+             singletonset_slot_reads_total{type=\"u32\",type_name=\"u32\"} 1\n"
+        ));
+        assert!(text.contains("singletonset_slot_writes_total{type=\"u32\",type_name=\"u32\"} 0\n"));
+        assert!(text.contains(
+            "singletonset_slot_inserted_timestamp_seconds{type=\"u32\",type_name=\"u32\"} 1.5\n"
+        ));
+        assert_eq!(escape_label("a\"b\\c"), "a\\\"b\\\\c");
+    }
+
+    #[test]
+    fn initialization_is_recorded_after_the_initializer_runs() {
+        let seconds = Rc::new(Cell::new(10));
+        let clock = Rc::clone(&seconds);
+
+        let mut set = SingletonSet::new();
+        set.set_clock(move || UNIX_EPOCH + Duration::from_secs(clock.get()));
+        set.get_or_insert_with(|| {
+            seconds.set(20);
+            1u8
+        });
+
+        let stats = set.stats_for::<u8>().unwrap();
+        assert_eq!(stats.inserted_at(), UNIX_EPOCH + Duration::from_secs(20));
+        assert_eq!(stats.writes(), 0);
+    }
+
+    #[test]
+    fn failed_mutable_lookups_are_not_counted() {
+        let mut set = SingletonSet::new();
+        set.insert_pinned(1u8);
+
+        assert!(set.try_as_mut::<u8>().is_none());
+        assert!(set.get_by_type_mut(&Type::of::<u8>()).is_none());
+        assert_eq!(set.stats_for::<u8>().unwrap().writes(), 0);
+    }
+
+    #[test]
+    fn prometheus_labels_keep_generic_arguments() {
+        let mut set = SingletonSet::new();
+        set.insert(vec![1u8]);
+        set.insert(vec![String::new()]);
+
+        let text = set.stats().to_prometheus();
+        assert!(text.contains(
+            "singletonset_slot_reads_total{type=\"Vec\",type_name=\"alloc::vec::Vec<u8>\"} 0\n"
+        ));
+        assert!(text.contains(
+            "singletonset_slot_reads_total{type=\"Vec\",type_name=\"alloc::vec::Vec<alloc::string::String>\"} 0\n"
+        ));
+    }
+}
@@ -0,0 +1,1059 @@
+//! A structured view of the type names reported by the compiler.
+//!
+//! [`Type::as_str()`] returns whatever [`std::any::type_name()`] reports,
+//! which is a single string such as
+//! `"alloc::vec::Vec<alloc::string::String>"`. The [`TypeName`] returned by
+//! [`Type::parsed_name()`] breaks that string down into its parts, so the
+//! crate, module path, base name and generic arguments of a type can each
+//! be inspected, and the name can be displayed in a shorter form.
+//!
+//! The format of type names is not specified by the compiler, so the
+//! parser is lenient. Anything it does not understand is kept as
+//! [`TypeName::Unknown`], which still displays exactly as reported.
+//!
+//! [`Type::as_str()`]: crate::Type::as_str()
+//! [`Type::parsed_name()`]: crate::Type::parsed_name()
+
+use std::fmt::{Display, Formatter, Result};
+
+/// The parsed form of a type name.
+///
+/// The [`Display`] implementation writes the fully qualified name, which
+/// is identical to the name it was parsed from for names reported by the
+/// compiler. Use [`.short()`] to display the name without module paths.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::Type;
+///
+/// let t = Type::of::<Vec<Option<String>>>();
+/// let name = t.parsed_name();
+///
+/// assert_eq!(name.crate_name(), Some("alloc"));
+/// assert_eq!(name.module_path(), Some("alloc::vec"));
+/// assert_eq!(name.base_name(), Some("Vec"));
+/// assert_eq!(name.short().to_string(), "Vec<Option<String>>");
+/// assert_eq!(name.to_string(), t.as_str());
+/// ```
+///
+/// [`.short()`]: Self::short()
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TypeName<'a> {
+    /// A named type, such as `alloc::string::String` or `u8`.
+    Path(TypePath<'a>),
+    /// A reference, such as `&str` or `&mut [u8]`.
+    Reference {
+        /// The lifetime of the reference, if one was reported.
+        lifetime: Option<&'a str>,
+        /// Whether the reference is mutable.
+        mutable: bool,
+        /// The type being referenced.
+        inner: Box<TypeName<'a>>,
+    },
+    /// A raw pointer, such as `*const u8`.
+    Pointer {
+        /// Whether the pointer is `*mut` rather than `*const`.
+        mutable: bool,
+        /// The type being pointed to.
+        inner: Box<TypeName<'a>>,
+    },
+    /// A slice, such as `[u8]`.
+    Slice(Box<TypeName<'a>>),
+    /// An array, such as `[u8; 4]`.
+    Array {
+        /// The type of each element.
+        element: Box<TypeName<'a>>,
+        /// The length of the array, as reported.
+        len: &'a str,
+    },
+    /// A tuple, including the unit type `()`.
+    Tuple(Vec<TypeName<'a>>),
+    /// A function pointer, such as `fn(u8) -> u8`.
+    FnPointer(FnSignature<'a>),
+    /// A trait object, such as `dyn Any + Send`.
+    TraitObject(Vec<Bound<'a>>),
+    /// The never type, `!`.
+    Never,
+    /// A name that could not be parsed.
+    Unknown(&'a str),
+}
+
+/// A path naming a type, such as `alloc::vec::Vec<u8>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypePath<'a> {
+    segments: Vec<PathSegment<'a>>,
+    module: Option<&'a str>,
+}
+
+/// One segment of a [`TypePath`], along with its generic arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathSegment<'a> {
+    name: &'a str,
+    args: GenericArgs<'a>,
+}
+
+/// The generic arguments of a [`PathSegment`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GenericArgs<'a> {
+    /// The segment has no generic arguments.
+    None,
+    /// Arguments in angle brackets, such as `<u8, 4>`.
+    AngleBracketed(Vec<GenericArg<'a>>),
+    /// The function-like arguments of the `Fn` traits, such as
+    /// `(u8) -> u8`.
+    Parenthesized(FnSignature<'a>),
+}
+
+/// A single generic argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GenericArg<'a> {
+    /// A lifetime, such as `'_`.
+    Lifetime(&'a str),
+    /// A type.
+    Type(TypeName<'a>),
+    /// A constant value, as reported.
+    Const(&'a str),
+    /// An associated type binding, such as `Item = u8`.
+    Binding {
+        /// The name of the associated type.
+        name: &'a str,
+        /// The type it is bound to.
+        ty: TypeName<'a>,
+    },
+}
+
+/// The inputs and output of a function pointer or `Fn` trait.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FnSignature<'a> {
+    qualifiers: &'a str,
+    inputs: Vec<TypeName<'a>>,
+    variadic: bool,
+    output: Option<Box<TypeName<'a>>>,
+}
+
+/// A bound of a trait object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Bound<'a> {
+    /// A trait, such as `core::marker::Send`.
+    Trait(TypePath<'a>),
+    /// A lifetime, such as `'static`.
+    Lifetime(&'a str),
+}
+
+impl<'a> TypeName<'a> {
+    /// Parses a type name.
+    ///
+    /// This never fails. Names that cannot be parsed are returned as
+    /// [`TypeName::Unknown`].
+    pub fn parse(name: &'a str) -> Self {
+        let mut parser = Parser {
+            src: name,
+            pos: 0,
+            depth: 0,
+        };
+
+        match parser.ty(true) {
+            Some(ty) if parser.at_end() => ty,
+            _ => TypeName::Unknown(name),
+        }
+    }
+
+    /// Returns the path of a named type.
+    ///
+    /// References, pointers, slices and arrays return the path of the type
+    /// they contain, and trait objects return the path of their first
+    /// trait. Other types have no path.
+    pub fn path(&self) -> Option<&TypePath<'a>> {
+        match self {
+            TypeName::Path(path) => Some(path),
+            TypeName::Reference { inner, .. }
+            | TypeName::Pointer { inner, .. }
+            | TypeName::Slice(inner)
+            | TypeName::Array { element: inner, .. } => inner.path(),
+            TypeName::TraitObject(bounds) => bounds.iter().find_map(|bound| match bound {
+                Bound::Trait(path) => Some(path),
+                Bound::Lifetime(_) => None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the crate the type is defined in, if the name
+    /// includes one.
+    ///
+    /// Primitive types, such as `u8`, have no crate name.
+    pub fn crate_name(&self) -> Option<&'a str> {
+        self.path().and_then(TypePath::crate_name)
+    }
+
+    /// Returns the path of the module the type is defined in, if the name
+    /// includes one.
+    pub fn module_path(&self) -> Option<&'a str> {
+        self.path().and_then(TypePath::module_path)
+    }
+
+    /// Returns the name of the type without its module path or generic
+    /// arguments, such as `Vec` for `alloc::vec::Vec<u8>`.
+    pub fn base_name(&self) -> Option<&'a str> {
+        self.path().map(TypePath::base_name)
+    }
+
+    /// Returns the generic arguments of the type, such as `u8` for
+    /// `alloc::vec::Vec<u8>`.
+    pub fn generic_args(&self) -> &[GenericArg<'a>] {
+        self.path().map_or(&[], TypePath::generic_args)
+    }
+
+    /// Returns a value that displays the name without module paths, such as
+    /// `Vec<String>` for `alloc::vec::Vec<alloc::string::String>`.
+    pub fn short(&self) -> Short<'_, 'a> {
+        Short(self)
+    }
+
+    /// Returns a value that displays the fully qualified name.
+    ///
+    /// This is the same as displaying the `TypeName` itself.
+    pub fn qualified(&self) -> &Self {
+        self
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, short: bool) -> Result {
+        match self {
+            TypeName::Path(path) => path.write(f, short),
+            TypeName::Reference {
+                lifetime,
+                mutable,
+                inner,
+            } => {
+                f.write_str("&")?;
+                if let Some(lifetime) = lifetime {
+                    write!(f, "{} ", lifetime)?;
+                }
+                if *mutable {
+                    f.write_str("mut ")?;
+                }
+                inner.write(f, short)
+            }
+            TypeName::Pointer { mutable, inner } => {
+                f.write_str(if *mutable { "*mut " } else { "*const " })?;
+                inner.write(f, short)
+            }
+            TypeName::Slice(inner) => {
+                f.write_str("[")?;
+                inner.write(f, short)?;
+                f.write_str("]")
+            }
+            TypeName::Array { element, len } => {
+                f.write_str("[")?;
+                element.write(f, short)?;
+                write!(f, "; {}]", len)
+            }
+            TypeName::Tuple(elements) => {
+                f.write_str("(")?;
+                write_list(f, elements, short)?;
+                if elements.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            TypeName::FnPointer(signature) => {
+                f.write_str(signature.qualifiers)?;
+                if !signature.qualifiers.is_empty() {
+                    f.write_str(" ")?;
+                }
+                f.write_str("fn")?;
+                signature.write(f, short)
+            }
+            TypeName::TraitObject(bounds) => {
+                f.write_str("dyn ")?;
+                for (i, bound) in bounds.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" + ")?;
+                    }
+                    match bound {
+                        Bound::Trait(path) => path.write(f, short)?,
+                        Bound::Lifetime(lifetime) => f.write_str(lifetime)?,
+                    }
+                }
+                Ok(())
+            }
+            TypeName::Never => f.write_str("!"),
+            TypeName::Unknown(name) => f.write_str(name),
+        }
+    }
+}
+
+impl Display for TypeName<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.write(f, false)
+    }
+}
+
+/// Displays a [`TypeName`] without module paths.
+///
+/// This is returned by [`TypeName::short()`].
+#[derive(Clone, Copy, Debug)]
+pub struct Short<'n, 'a>(&'n TypeName<'a>);
+
+impl Display for Short<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.0.write(f, true)
+    }
+}
+
+impl<'a> TypePath<'a> {
+    /// Returns each segment of the path.
+    pub fn segments(&self) -> &[PathSegment<'a>] {
+        &self.segments
+    }
+
+    /// Returns the first segment of the path, if the path has more than
+    /// one segment.
+    pub fn crate_name(&self) -> Option<&'a str> {
+        match self.segments.as_slice() {
+            [first, _, ..] => Some(first.name),
+            _ => None,
+        }
+    }
+
+    /// Returns every segment of the path except the last one, if the path
+    /// has more than one segment.
+    pub fn module_path(&self) -> Option<&'a str> {
+        self.module
+    }
+
+    /// Returns the name of the last segment of the path.
+    pub fn base_name(&self) -> &'a str {
+        self.last().name
+    }
+
+    /// Returns the angle-bracketed generic arguments of the last segment of
+    /// the path.
+    pub fn generic_args(&self) -> &[GenericArg<'a>] {
+        match &self.last().args {
+            GenericArgs::AngleBracketed(args) => args,
+            _ => &[],
+        }
+    }
+
+    fn last(&self) -> &PathSegment<'a> {
+        self.segments
+            .last()
+            // Safety: The parser never creates a path without segments.
+            .unwrap()
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, short: bool) -> Result {
+        // The short form keeps only the last segment, unless that segment is
+        // generated by the compiler, like `{{closure}}`. Then it keeps every
+        // segment back to the item that defines it.
+        let skip = if short {
+            self.segments
+                .iter()
+                .rposition(|segment| !segment.name.starts_with("{{"))
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        for (i, segment) in self.segments.iter().enumerate().skip(skip) {
+            if i > skip {
+                f.write_str("::")?;
+            }
+            segment.write(f, short)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> PathSegment<'a> {
+    /// Returns the name of the segment.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns the generic arguments of the segment.
+    pub fn args(&self) -> &GenericArgs<'a> {
+        &self.args
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, short: bool) -> Result {
+        f.write_str(self.name)?;
+
+        match &self.args {
+            GenericArgs::None => Ok(()),
+            GenericArgs::AngleBracketed(args) => {
+                f.write_str("<")?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match arg {
+                        GenericArg::Lifetime(value) | GenericArg::Const(value) => {
+                            f.write_str(value)?
+                        }
+                        GenericArg::Type(ty) => ty.write(f, short)?,
+                        GenericArg::Binding { name, ty } => {
+                            write!(f, "{} = ", name)?;
+                            ty.write(f, short)?;
+                        }
+                    }
+                }
+                f.write_str(">")
+            }
+            GenericArgs::Parenthesized(signature) => signature.write(f, short),
+        }
+    }
+}
+
+impl<'a> FnSignature<'a> {
+    /// Returns the qualifiers of a function pointer, such as
+    /// `unsafe extern "C"`, or an empty string.
+    pub fn qualifiers(&self) -> &'a str {
+        self.qualifiers
+    }
+
+    /// Returns the type of each input.
+    pub fn inputs(&self) -> &[TypeName<'a>] {
+        &self.inputs
+    }
+
+    /// Returns true if the function takes a variable number of arguments.
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+
+    /// Returns the output type, if it is not `()`.
+    pub fn output(&self) -> Option<&TypeName<'a>> {
+        self.output.as_deref()
+    }
+
+    fn write(&self, f: &mut Formatter<'_>, short: bool) -> Result {
+        f.write_str("(")?;
+        write_list(f, &self.inputs, short)?;
+        if self.variadic {
+            f.write_str(if self.inputs.is_empty() {
+                "..."
+            } else {
+                ", ..."
+            })?;
+        }
+        f.write_str(")")?;
+        if let Some(output) = &self.output {
+            f.write_str(" -> ")?;
+            output.write(f, short)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_list(f: &mut Formatter<'_>, types: &[TypeName<'_>], short: bool) -> Result {
+    for (i, ty) in types.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        ty.write(f, short)?;
+    }
+    Ok(())
+}
+
+/// Deeply nested names are treated as unknown rather than risk exhausting
+/// the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.pos == self.src.len()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if rest.starts_with(keyword) && !rest[keyword.len()..].starts_with(is_ident_char) {
+            self.pos += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Option<()> {
+        if self.eat(token) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Parses an identifier, or a compiler-generated segment such as
+    /// `{{closure}}`.
+    fn ident(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let rest = self.rest();
+
+        let len = if rest.starts_with("{{") {
+            rest.find("}}")? + 2
+        } else {
+            let prefix = if rest.starts_with("r#") { 2 } else { 0 };
+            let len = rest[prefix..]
+                .find(|c| !is_ident_char(c))
+                .unwrap_or(rest.len() - prefix);
+            if len == 0 || rest[prefix..].starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            prefix + len
+        };
+
+        self.pos += len;
+        Some(&rest[..len])
+    }
+
+    /// Returns true if the next token is a lifetime rather than a character
+    /// literal.
+    fn at_lifetime(&mut self) -> bool {
+        self.skip_whitespace();
+        let mut chars = self.rest().chars();
+        chars.next() == Some('\'')
+            && chars.next().map_or(false, is_ident_char)
+            && chars.next() != Some('\'')
+    }
+
+    fn lifetime(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+        self.expect("'")?;
+        self.ident()?;
+        Some(&self.src[start..self.pos])
+    }
+
+    /// Parses a constant generic argument or array length as an opaque
+    /// string, up to the next `,`, `>` or `]` that isn't nested.
+    fn constant(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut depth = 0usize;
+        let mut chars = self.rest().chars();
+        let mut end = start;
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => {
+                    // A character literal, which may hold an escaped quote.
+                    let mut escaped = false;
+                    loop {
+                        match chars.next()? {
+                            '\'' if !escaped => break,
+                            '\\' if !escaped => escaped = true,
+                            _ => escaped = false,
+                        }
+                    }
+                }
+                '{' | '(' | '[' | '<' => depth += 1,
+                '}' | ')' | ']' | '>' if depth > 0 => depth -= 1,
+                ',' | '>' | ']' if depth == 0 => break,
+                _ => {}
+            }
+            end = self.src.len() - chars.as_str().len();
+        }
+
+        self.pos = end;
+        let value = self.src[start..end].trim_end();
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    fn ty(&mut self, allow_plus: bool) -> Option<TypeName<'a>> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let ty = self.ty_inner(allow_plus);
+        self.depth -= 1;
+        ty
+    }
+
+    fn ty_inner(&mut self, allow_plus: bool) -> Option<TypeName<'a>> {
+        match self.peek()? {
+            '&' => {
+                self.pos += 1;
+                let lifetime = if self.at_lifetime() {
+                    Some(self.lifetime()?)
+                } else {
+                    None
+                };
+                let mutable = self.eat_keyword("mut");
+                let inner = Box::new(self.ty(allow_plus)?);
+                Some(TypeName::Reference {
+                    lifetime,
+                    mutable,
+                    inner,
+                })
+            }
+            '*' => {
+                self.pos += 1;
+                let mutable = if self.eat_keyword("mut") {
+                    true
+                } else if self.eat_keyword("const") {
+                    false
+                } else {
+                    return None;
+                };
+                let inner = Box::new(self.ty(allow_plus)?);
+                Some(TypeName::Pointer { mutable, inner })
+            }
+            '[' => {
+                self.pos += 1;
+                let element = Box::new(self.ty(true)?);
+                let ty = if self.eat(";") {
+                    let len = self.constant()?;
+                    TypeName::Array { element, len }
+                } else {
+                    TypeName::Slice(element)
+                };
+                self.expect("]")?;
+                Some(ty)
+            }
+            '(' => {
+                self.pos += 1;
+                let mut elements = Vec::new();
+                let mut trailing_comma = false;
+                while !self.eat(")") {
+                    elements.push(self.ty(true)?);
+                    trailing_comma = self.eat(",");
+                    if !trailing_comma {
+                        self.expect(")")?;
+                        break;
+                    }
+                }
+                // A single type in parentheses is not a tuple, and is never
+                // reported by the compiler.
+                if elements.len() == 1 && !trailing_comma {
+                    return None;
+                }
+                Some(TypeName::Tuple(elements))
+            }
+            '!' => {
+                self.pos += 1;
+                Some(TypeName::Never)
+            }
+            _ if self.eat_keyword("dyn") => {
+                let mut bounds = Vec::new();
+                loop {
+                    if self.at_lifetime() {
+                        bounds.push(Bound::Lifetime(self.lifetime()?));
+                    } else {
+                        bounds.push(Bound::Trait(self.path(true)?));
+                    }
+                    if !allow_plus || !self.eat("+") {
+                        break;
+                    }
+                }
+                Some(TypeName::TraitObject(bounds))
+            }
+            _ if self.at_fn_pointer() => {
+                self.skip_whitespace();
+                let start = self.pos;
+                self.eat_keyword("unsafe");
+                if self.eat_keyword("extern") {
+                    self.abi()?;
+                }
+                let qualifiers = self.src[start..self.pos].trim_end();
+                if !self.eat_keyword("fn") {
+                    return None;
+                }
+                let mut signature = self.signature()?;
+                signature.qualifiers = qualifiers;
+                Some(TypeName::FnPointer(signature))
+            }
+            _ => self.path(false).map(TypeName::Path),
+        }
+    }
+
+    fn at_fn_pointer(&mut self) -> bool {
+        let pos = self.pos;
+        let found =
+            self.eat_keyword("unsafe") || self.eat_keyword("extern") || self.eat_keyword("fn");
+        self.pos = pos;
+        found
+    }
+
+    /// Parses the optional string literal naming the ABI of an `extern`
+    /// function.
+    fn abi(&mut self) -> Option<()> {
+        if self.eat("\"") {
+            let len = self.rest().find('"')?;
+            self.pos += len + 1;
+        }
+        Some(())
+    }
+
+    /// Parses the parenthesized inputs and the output of a function pointer
+    /// or `Fn` trait.
+    fn signature(&mut self) -> Option<FnSignature<'a>> {
+        self.expect("(")?;
+
+        let mut inputs = Vec::new();
+        let mut variadic = false;
+        while !self.eat(")") {
+            if self.eat("...") {
+                variadic = true;
+            } else {
+                inputs.push(self.ty(true)?);
+            }
+            if !self.eat(",") {
+                self.expect(")")?;
+                break;
+            }
+        }
+
+        let output = if self.eat("->") {
+            Some(Box::new(self.ty(false)?))
+        } else {
+            None
+        };
+
+        Some(FnSignature {
+            qualifiers: "",
+            inputs,
+            variadic,
+            output,
+        })
+    }
+
+    fn path(&mut self, in_bound: bool) -> Option<TypePath<'a>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let mut module_end = None;
+        let mut segments = Vec::new();
+
+        loop {
+            let name = self.ident()?;
+            let args = if self.rest().starts_with('<') || self.rest().starts_with("::<") {
+                self.eat("::");
+                self.pos += 1;
+                GenericArgs::AngleBracketed(self.generic_args()?)
+            } else if in_bound && self.rest().starts_with('(') {
+                GenericArgs::Parenthesized(self.signature()?)
+            } else {
+                GenericArgs::None
+            };
+            segments.push(PathSegment { name, args });
+
+            let end = self.pos;
+            if !self.eat("::") {
+                break;
+            }
+            module_end = Some(end);
+        }
+
+        Some(TypePath {
+            segments,
+            module: module_end.map(|end| &self.src[start..end]),
+        })
+    }
+
+    /// Parses the generic arguments after an opening `<`.
+    fn generic_args(&mut self) -> Option<Vec<GenericArg<'a>>> {
+        let mut args = Vec::new();
+
+        while !self.eat(">") {
+            args.push(self.generic_arg()?);
+            if !self.eat(",") {
+                self.expect(">")?;
+                break;
+            }
+        }
+
+        Some(args)
+    }
+
+    fn generic_arg(&mut self) -> Option<GenericArg<'a>> {
+        if self.at_lifetime() {
+            return self.lifetime().map(GenericArg::Lifetime);
+        }
+
+        match self.peek()? {
+            '\'' | '{' | '-' | '0'..='9' => return self.constant().map(GenericArg::Const),
+            _ => {}
+        }
+
+        let pos = self.pos;
+        if self.eat_keyword("true") || self.eat_keyword("false") {
+            self.pos = pos;
+            return self.constant().map(GenericArg::Const);
+        }
+
+        if let Some(name) = self.ident() {
+            if self.eat("=") {
+                let ty = self.ty(true)?;
+                return Some(GenericArg::Binding { name, ty });
+            }
+            self.pos = pos;
+        }
+
+        self.ty(true).map(GenericArg::Type)
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c == '_' || c.is_alphanumeric()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Type;
+
+    /// Checks that the name of each type survives being parsed and written
+    /// back out, and that it was fully understood by the parser.
+    macro_rules! assert_round_trip {
+        ($($t:ty),+ $(,)?) => {$(
+            let name = std::any::type_name::<$t>();
+            let parsed = TypeName::parse(name);
+            assert!(!contains_unknown(&parsed), "failed to parse {}", name);
+            assert_eq!(parsed.to_string(), name);
+
+            let short = parsed.short().to_string();
+            assert_eq!(TypeName::parse(&short).short().to_string(), short);
+        )+};
+    }
+
+    /// Wraps each type in a variety of other types, then checks them all.
+    macro_rules! assert_round_trip_nested {
+        ($($t:ty),+ $(,)?) => {$(
+            assert_round_trip!(
+                $t,
+                &$t,
+                &mut [$t],
+                *const $t,
+                [$t; 3],
+                ($t,),
+                ($t, &$t, [($t, u8); 2]),
+                Vec<$t>,
+                Option<Box<$t>>,
+                std::collections::HashMap<$t, Vec<$t>>,
+                fn($t) -> $t,
+                fn(&$t, $t),
+                unsafe extern "C" fn($t) -> Option<$t>,
+                Box<dyn Fn($t) -> $t + Send + Sync>,
+                Box<dyn Iterator<Item = $t>>,
+                std::marker::PhantomData<fn() -> fn($t)>,
+            );
+        )+};
+    }
+
+    fn contains_unknown(name: &TypeName<'_>) -> bool {
+        fn path(path: &TypePath<'_>) -> bool {
+            path.segments().iter().any(|segment| match segment.args() {
+                GenericArgs::None => false,
+                GenericArgs::AngleBracketed(args) => args.iter().any(|arg| match arg {
+                    GenericArg::Type(ty) | GenericArg::Binding { ty, .. } => contains_unknown(ty),
+                    _ => false,
+                }),
+                GenericArgs::Parenthesized(s) => signature(s),
+            })
+        }
+
+        fn signature(signature: &FnSignature<'_>) -> bool {
+            signature.inputs().iter().any(contains_unknown)
+                || signature.output().map_or(false, contains_unknown)
+        }
+
+        match name {
+            TypeName::Path(p) => path(p),
+            TypeName::Reference { inner, .. }
+            | TypeName::Pointer { inner, .. }
+            | TypeName::Slice(inner)
+            | TypeName::Array { element: inner, .. } => contains_unknown(inner),
+            TypeName::Tuple(elements) => elements.iter().any(contains_unknown),
+            TypeName::FnPointer(s) => signature(s),
+            TypeName::TraitObject(bounds) => bounds.iter().any(|bound| match bound {
+                Bound::Trait(p) => path(p),
+                Bound::Lifetime(_) => false,
+            }),
+            TypeName::Never => false,
+            TypeName::Unknown(_) => true,
+        }
+    }
+
+    trait Logger {}
+    struct Konst<const N: i32>;
+    struct Letter<const C: char>;
+    struct Flag<const B: bool>;
+
+    #[test]
+    #[allow(clippy::unused_unit)]
+    fn real_type_names_round_trip() {
+        assert_round_trip_nested!(
+            u8,
+            (),
+            &'static str,
+            String,
+            Box<dyn Logger>,
+            [u16; 0],
+            (u8, (u16, (u32,))),
+            Konst<-1>,
+            Letter<'\''>,
+            Letter<','>,
+            Letter<'>'>,
+            Flag<true>,
+            fn(fn(u8) -> u8) -> fn(),
+            std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+        );
+
+        assert_round_trip!(
+            str,
+            [u8],
+            dyn Logger,
+            dyn Logger + Send + 'static,
+            std::borrow::Cow<'static, str>,
+            *mut dyn Logger,
+            dyn for<'a> Fn(&'a u8) -> &'a u8,
+            unsafe extern "C" fn(i32, ...) -> i32,
+            extern "C" fn(),
+            fn() -> !,
+        );
+    }
+
+    #[test]
+    fn closures_round_trip() {
+        fn name_of<T>(_: &T) -> &'static str {
+            std::any::type_name::<T>()
+        }
+
+        fn make<T: 'static>() -> impl Fn() -> Option<T> {
+            || None
+        }
+
+        let closure = |x: u8| x;
+        let map = std::iter::once(1u8).map(closure);
+
+        for name in [
+            name_of(&closure),
+            name_of(&make::<Vec<u8>>()),
+            name_of(&map),
+        ] {
+            let parsed = TypeName::parse(name);
+            assert!(!contains_unknown(&parsed), "failed to parse {}", name);
+            assert_eq!(parsed.to_string(), name);
+        }
+
+        let short = TypeName::parse(name_of(&closure)).short().to_string();
+        assert!(short.ends_with("::{{closure}}"), "{}", short);
+        assert!(!short.contains("singletonset::"), "{}", short);
+    }
+
+    #[test]
+    fn parts_of_paths() {
+        let name = TypeName::parse("alloc::vec::Vec<alloc::string::String>");
+
+        assert_eq!(name.crate_name(), Some("alloc"));
+        assert_eq!(name.module_path(), Some("alloc::vec"));
+        assert_eq!(name.base_name(), Some("Vec"));
+        assert_eq!(name.short().to_string(), "Vec<String>");
+
+        match name.generic_args() {
+            [GenericArg::Type(arg)] => {
+                assert_eq!(arg.module_path(), Some("alloc::string"));
+                assert_eq!(arg.base_name(), Some("String"));
+            }
+            args => panic!("unexpected generic arguments: {:?}", args),
+        }
+
+        let name = TypeName::parse("&mut [u8; 4]");
+        assert_eq!(name.crate_name(), None);
+        assert_eq!(name.base_name(), Some("u8"));
+        assert_eq!(name.short().to_string(), "&mut [u8; 4]");
+
+        let name =
+            TypeName::parse("dyn core::ops::function::Fn(&'_ u8) -> u8 + core::marker::Send");
+        assert_eq!(name.base_name(), Some("Fn"));
+        assert_eq!(name.short().to_string(), "dyn Fn(&'_ u8) -> u8 + Send");
+
+        assert_eq!(TypeName::parse("(u8, u16)").base_name(), None);
+        assert_eq!(TypeName::parse("foo<").to_string(), "foo<");
+        assert_eq!(TypeName::parse("foo<"), TypeName::Unknown("foo<"));
+    }
+
+    #[test]
+    fn mangled_names_never_panic() {
+        let names = [
+            Type::of::<std::collections::HashMap<String, Vec<(u8, [i32; 2])>>>(),
+            Type::of::<Box<dyn Fn(&u8) -> Option<&'static str> + Send>>(),
+            Type::of::<unsafe extern "C" fn(i32, ...) -> i32>(),
+            Type::of::<Letter<'\''>>(),
+            Type::of::<&mut *const [Konst<-7>]>(),
+        ];
+
+        // A small, deterministic generator is enough to shuffle names into
+        // a wide variety of malformed inputs.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+
+        for t in names.iter().cycle().take(2000) {
+            let chars: Vec<char> = t.as_str().chars().collect();
+            let mut mangled = String::new();
+
+            for _ in 0..(1 + next(4)) {
+                let start = next(chars.len());
+                let end = start + next(chars.len() - start + 1);
+                mangled.extend(&chars[start..end]);
+                mangled.push(['<', '>', '(', ')', '\'', ',', ' ', ':'][next(8)]);
+            }
+
+            let parsed = TypeName::parse(&mangled);
+            let _ = parsed.short().to_string();
+
+            // Anything that parses must parse the same way again.
+            let written = parsed.to_string();
+            assert_eq!(TypeName::parse(&written).to_string(), written);
+        }
+
+        let deep = "&".repeat(10_000) + "u8";
+        assert_eq!(TypeName::parse(&deep), TypeName::Unknown(&deep));
+    }
+}
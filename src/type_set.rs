@@ -0,0 +1,262 @@
+//! A set of [`Type`]s, and the iterators over it.
+//!
+//! [`TypeSet`] is re-exported from the crate root. Its iterators live here,
+//! so they don't clash with the iterators over a [`SingletonSet`].
+//!
+//! [`SingletonSet`]: crate::SingletonSet
+
+use indexmap::IndexSet;
+
+use crate::Type;
+
+/// An owned set of [`Type`]s.
+///
+/// Unlike [`Types`], which borrows from a [`SingletonSet`], a `TypeSet` owns
+/// its contents, so it can be kept around, compared with other sets, and
+/// combined using the usual set operations. Like the [`SingletonSet`]
+/// itself, a `TypeSet` remembers the order in which its types were
+/// inserted.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::{SingletonSet, Type, TypeSet};
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(1u8);
+/// set.insert("Hello".to_string());
+///
+/// let required: TypeSet = [Type::of::<u8>(), Type::of::<u16>()]
+///     .into_iter()
+///     .collect();
+///
+/// let missing = required.difference(&set.type_set());
+///
+/// assert!(missing.contains::<u16>());
+/// assert!(!missing.contains::<u8>());
+/// ```
+///
+/// [`Types`]: crate::Types
+/// [`SingletonSet`]: crate::SingletonSet
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeSet(IndexSet<Type>);
+
+impl TypeSet {
+    /// Creates an empty `TypeSet`.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        TypeSet(IndexSet::new())
+    }
+
+    /// Creates an empty `TypeSet` with at least the specified capacity.
+    #[inline]
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        TypeSet(IndexSet::with_capacity(capacity))
+    }
+
+    /// Returns the number of types in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the set contains no types.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Adds a [`Type`] to the set.
+    ///
+    /// Returns true if the type was not already in the set. If it was, the
+    /// set is unchanged and keeps the type in its original position.
+    pub fn insert(&mut self, t: Type) -> bool {
+        self.0.insert(t)
+    }
+
+    /// Adds the [`Type`] of `T` to the set.
+    ///
+    /// Returns true if the type was not already in the set.
+    pub fn insert_type<T>(&mut self) -> bool
+    where
+        T: 'static,
+    {
+        self.insert(Type::of::<T>())
+    }
+
+    /// Removes a [`Type`] from the set, preserving the order of the
+    /// remaining types.
+    ///
+    /// Returns true if the type was in the set.
+    pub fn remove(&mut self, t: &Type) -> bool {
+        self.0.shift_remove(t)
+    }
+
+    /// Returns true if the type `T` is in the set.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.0.contains(&Type::of::<T>())
+    }
+
+    /// Returns true if the supplied [`Type`] is in the set.
+    pub fn contains_type(&self, t: &Type) -> bool {
+        self.0.contains(t)
+    }
+
+    /// Returns an iterator that visits each [`Type`] in insertion order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter())
+    }
+
+    /// Returns a new set holding every type that is in `self`, `other`, or
+    /// both.
+    ///
+    /// Types from `self` come first, followed by the types that only
+    /// `other` holds.
+    #[must_use]
+    pub fn union(&self, other: &TypeSet) -> TypeSet {
+        TypeSet(self.0.union(&other.0).copied().collect())
+    }
+
+    /// Returns a new set holding the types that are in both `self` and
+    /// `other`, in the order they appear in `self`.
+    #[must_use]
+    pub fn intersection(&self, other: &TypeSet) -> TypeSet {
+        TypeSet(self.0.intersection(&other.0).copied().collect())
+    }
+
+    /// Returns a new set holding the types that are in `self` but not in
+    /// `other`, in the order they appear in `self`.
+    #[must_use]
+    pub fn difference(&self, other: &TypeSet) -> TypeSet {
+        TypeSet(self.0.difference(&other.0).copied().collect())
+    }
+
+    /// Returns true if every type in `self` is also in `other`.
+    pub fn is_subset(&self, other: &TypeSet) -> bool {
+        self.0.is_subset(&other.0)
+    }
+
+    /// Returns true if every type in `other` is also in `self`.
+    pub fn is_superset(&self, other: &TypeSet) -> bool {
+        self.0.is_superset(&other.0)
+    }
+
+    /// Returns true if `self` and `other` have no types in common.
+    pub fn is_disjoint(&self, other: &TypeSet) -> bool {
+        self.0.is_disjoint(&other.0)
+    }
+}
+
+impl FromIterator<Type> for TypeSet {
+    fn from_iter<I: IntoIterator<Item = Type>>(iter: I) -> Self {
+        TypeSet(iter.into_iter().collect())
+    }
+}
+
+impl<'a> FromIterator<&'a Type> for TypeSet {
+    fn from_iter<I: IntoIterator<Item = &'a Type>>(iter: I) -> Self {
+        TypeSet(iter.into_iter().copied().collect())
+    }
+}
+
+impl Extend<Type> for TypeSet {
+    fn extend<I: IntoIterator<Item = Type>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<'a> Extend<&'a Type> for TypeSet {
+    fn extend<I: IntoIterator<Item = &'a Type>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().copied())
+    }
+}
+
+impl<'a> IntoIterator for &'a TypeSet {
+    type Item = &'a Type;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for TypeSet {
+    type Item = Type;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+/// An iterator over the [`Type`]s in a [`TypeSet`].
+pub struct Iter<'a>(indexmap::set::Iter<'a, Type>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Type;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// An owning iterator over the [`Type`]s in a [`TypeSet`].
+pub struct IntoIter(indexmap::set::IntoIter<Type>);
+
+impl Iterator for IntoIter {
+    type Item = Type;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_of(types: &[Type]) -> TypeSet {
+        types.iter().collect()
+    }
+
+    #[test]
+    fn type_set_algebra_preserves_order() {
+        let a = set_of(&[Type::of::<u8>(), Type::of::<u16>(), Type::of::<u32>()]);
+        let b = set_of(&[Type::of::<u64>(), Type::of::<u32>()]);
+
+        let union: Vec<_> = a.union(&b).into_iter().collect();
+        assert_eq!(
+            union,
+            [
+                Type::of::<u8>(),
+                Type::of::<u16>(),
+                Type::of::<u32>(),
+                Type::of::<u64>()
+            ]
+        );
+
+        let intersection: Vec<_> = a.intersection(&b).into_iter().collect();
+        assert_eq!(intersection, [Type::of::<u32>()]);
+
+        let difference: Vec<_> = a.difference(&b).into_iter().collect();
+        assert_eq!(difference, [Type::of::<u8>(), Type::of::<u16>()]);
+    }
+
+    #[test]
+    fn type_set_subsets() {
+        let a = set_of(&[Type::of::<u8>(), Type::of::<u16>()]);
+        let b = set_of(&[Type::of::<u16>(), Type::of::<u8>(), Type::of::<u32>()]);
+
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
+        assert!(TypeSet::new().is_subset(&a));
+        assert!(a.contains::<u8>());
+        assert!(!a.contains::<u32>());
+    }
+}
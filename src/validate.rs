@@ -0,0 +1,425 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    sync::Arc,
+};
+
+use indexmap::IndexMap;
+
+use crate::{slot_panic, Type};
+
+/// The error a validator returns, boxed so that validators of different
+/// types can return different errors.
+pub type ValidationError = Box<dyn Error + Send + Sync>;
+
+type CheckFn = dyn Fn(&dyn Any) -> Result<(), ValidationError>;
+
+type CloneFn = fn(&dyn Any) -> Box<dyn Any>;
+
+/// What a [`SingletonSet`] does with a change that one of its validators
+/// fails, set with [`SingletonSet::set_validation_policy()`].
+///
+/// Either way, the failure is added to the set's [`ValidationReport`].
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::set_validation_policy()`]: crate::SingletonSet::set_validation_policy()
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ValidationPolicy {
+    /// The change is undone, and the slot keeps the value it held before.
+    #[default]
+    Reject,
+    /// The change is kept.
+    Report,
+}
+
+/// A value that failed the validator of its type.
+#[derive(Clone, Debug)]
+pub struct ValidationFailure {
+    t: Type,
+    error: Arc<dyn Error + Send + Sync>,
+    rejected: bool,
+}
+
+impl ValidationFailure {
+    /// Returns the [`Type`] of the value that failed validation.
+    pub fn to_type(&self) -> Type {
+        self.t
+    }
+
+    /// Returns the error the validator returned.
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+
+    /// Returns true if the change was undone because of the failure.
+    pub fn was_rejected(&self) -> bool {
+        self.rejected
+    }
+}
+
+impl Error for ValidationFailure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.error)
+    }
+}
+
+impl Display for ValidationFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let outcome = if self.rejected { "rejected" } else { "kept" };
+        write!(f, "{} ({}): {}", self.t, outcome, self.error)
+    }
+}
+
+/// Every validation failure a [`SingletonSet`] has recorded, oldest first.
+///
+/// The report is returned by [`SingletonSet::validation_report()`], and
+/// can be emptied with [`SingletonSet::take_validation_report()`].
+///
+/// [`SingletonSet`]: crate::SingletonSet
+/// [`SingletonSet::validation_report()`]: crate::SingletonSet::validation_report()
+/// [`SingletonSet::take_validation_report()`]: crate::SingletonSet::take_validation_report()
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    failures: Vec<ValidationFailure>,
+}
+
+impl ValidationReport {
+    /// Returns each recorded failure, oldest first.
+    pub fn failures(&self) -> &[ValidationFailure] {
+        &self.failures
+    }
+
+    /// Returns the number of recorded failures.
+    pub fn len(&self) -> usize {
+        self.failures.len()
+    }
+
+    /// Returns true if no failures were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for failure in &self.failures {
+            writeln!(f, "{}", failure)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The validator of one type, along with how to clone a value of that type
+/// so a rejected change can be undone, if the type can be cloned.
+struct Validator {
+    check: Box<CheckFn>,
+    clone: Option<CloneFn>,
+}
+
+fn clone_value<T>(value: &dyn Any) -> Box<dyn Any>
+where
+    T: Clone + 'static,
+{
+    let value: &T = value
+        .downcast_ref()
+        // Safety: This function is only stored alongside validators of
+        // type `T`, so this `expect()` will never panic.
+        .expect("a validator is only given values of its own type");
+    Box::new(value.clone())
+}
+
+/// The validators of a set, its policy, and the failures it has recorded.
+#[derive(Default)]
+pub(crate) struct Validators {
+    policy: ValidationPolicy,
+    validators: IndexMap<Type, Validator>,
+    report: ValidationReport,
+}
+
+impl Validators {
+    pub(crate) fn register<T, E>(&mut self, validate: impl Fn(&T) -> Result<(), E> + 'static)
+    where
+        T: Clone + 'static,
+        E: Into<ValidationError>,
+    {
+        self.register_with(validate, Some(clone_value::<T>));
+    }
+
+    pub(crate) fn register_without_undo<T, E>(
+        &mut self,
+        validate: impl Fn(&T) -> Result<(), E> + 'static,
+    ) where
+        T: 'static,
+        E: Into<ValidationError>,
+    {
+        self.register_with(validate, None);
+    }
+
+    fn register_with<T, E>(
+        &mut self,
+        validate: impl Fn(&T) -> Result<(), E> + 'static,
+        clone: Option<CloneFn>,
+    ) where
+        T: 'static,
+        E: Into<ValidationError>,
+    {
+        // Values inserted with `.insert_unsized()` are still boxed.
+        let check = move |value: &dyn Any| {
+            let value = value
+                .downcast_ref::<T>()
+                .or_else(|| value.downcast_ref::<Box<T>>().map(|value| &**value));
+
+            match value {
+                Some(value) => validate(value).map_err(Into::into),
+                None => Ok(()),
+            }
+        };
+
+        self.validators.insert(
+            Type::of::<T>(),
+            Validator {
+                check: Box::new(check),
+                clone,
+            },
+        );
+    }
+
+    pub(crate) fn unregister(&mut self, t: &Type) -> bool {
+        self.validators.shift_remove(t).is_some()
+    }
+
+    pub(crate) fn policy(&self) -> ValidationPolicy {
+        self.policy
+    }
+
+    pub(crate) fn set_policy(&mut self, policy: ValidationPolicy) {
+        self.policy = policy;
+    }
+
+    pub(crate) fn report(&self) -> &ValidationReport {
+        &self.report
+    }
+
+    pub(crate) fn take_report(&mut self) -> ValidationReport {
+        std::mem::take(&mut self.report)
+    }
+
+    /// Validates a value of `t`, recording any failure, and returns the
+    /// failure if the change that produced it should be undone.
+    pub(crate) fn validate(&mut self, t: &Type, value: &dyn Any) -> Result<(), ValidationFailure> {
+        self.validate_change(t, value, true)
+    }
+
+    /// Like [`.validate()`], but for a value that was changed in place, so
+    /// the change is only undone if it is `undoable`, and is otherwise kept
+    /// whatever the policy.
+    ///
+    /// [`.validate()`]: Self::validate()
+    pub(crate) fn validate_change(
+        &mut self,
+        t: &Type,
+        value: &dyn Any,
+        undoable: bool,
+    ) -> Result<(), ValidationFailure> {
+        let validator = match self.validators.get(t) {
+            Some(validator) => validator,
+            None => return Ok(()),
+        };
+
+        match (validator.check)(value) {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                let failure = ValidationFailure {
+                    t: *t,
+                    error: error.into(),
+                    rejected: undoable && self.policy == ValidationPolicy::Reject,
+                };
+                self.report.failures.push(failure.clone());
+                match failure.rejected {
+                    true => Err(failure),
+                    false => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Validates a value of `t` whose insertion can't be undone, such as the
+    /// first value of a slot or a pinned value, so a rejected value panics
+    /// instead.
+    pub(crate) fn check_or_panic(&mut self, t: &Type, value: &dyn Any) {
+        if let Err(failure) = self.validate(t, value) {
+            slot_panic(t, &format!("invalid value: {}", failure));
+        }
+    }
+
+    /// Returns a copy of the value of `t` to restore if a change to it is
+    /// rejected, or [`None`] if `t` has no validator or can't be cloned.
+    pub(crate) fn backup(&self, t: &Type, value: &dyn Any) -> Option<Box<dyn Any>> {
+        self.validators
+            .get(t)
+            .and_then(|validator| validator.clone)
+            .map(|clone| clone(value))
+    }
+}
+
+impl Debug for Validators {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Validators")
+            .field("policy", &self.policy)
+            .field("validators", &self.validators.keys())
+            .field("report", &self.report)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SingletonSet, Type, ValidationPolicy};
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct PoolConfig {
+        min: u32,
+        max: u32,
+    }
+
+    fn ordered(config: &PoolConfig) -> Result<(), &'static str> {
+        match config.max >= config.min {
+            true => Ok(()),
+            false => Err("max is less than min"),
+        }
+    }
+
+    #[test]
+    fn rejected_changes_are_undone() {
+        let mut set = SingletonSet::new();
+        set.register_validator(ordered);
+
+        set.insert(PoolConfig { min: 1, max: 4 });
+        assert_eq!(set.insert(PoolConfig { min: 5, max: 4 }), None);
+        assert_eq!(set.get::<PoolConfig>().min, 1);
+
+        set.with_mut(|config: &mut PoolConfig| config.max = 0);
+        set.with_mut_or_else(PoolConfig::default, |config| config.min = 2);
+        assert_eq!(set.get::<PoolConfig>(), &PoolConfig { min: 2, max: 4 });
+
+        let report = set.validation_report();
+        assert_eq!(report.len(), 2);
+        assert!(report.failures()[1].was_rejected());
+        assert_eq!(report.failures()[1].to_type(), Type::of::<PoolConfig>());
+        assert_eq!(
+            report.failures()[1].error().to_string(),
+            "max is less than min"
+        );
+
+        assert_eq!(set.take_validation_report().len(), 2);
+        assert!(set.validation_report().is_empty());
+    }
+
+    #[test]
+    fn reported_changes_are_kept() {
+        let mut set = SingletonSet::new();
+        set.register_validator(ordered);
+        set.set_validation_policy(ValidationPolicy::Report);
+
+        set.get_or_insert(PoolConfig { min: 3, max: 1 });
+        set.with_mut(|config: &mut PoolConfig| config.max = 2);
+        assert_eq!(set.get::<PoolConfig>(), &PoolConfig { min: 3, max: 2 });
+
+        let report = set.validation_report();
+        assert_eq!(report.len(), 2);
+        assert!(!report.failures()[0].was_rejected());
+        assert!(report
+            .to_string()
+            .ends_with("::PoolConfig (kept): max is less than min\n"));
+
+        assert!(set.unregister_validator::<PoolConfig>());
+        set.insert(PoolConfig { min: 9, max: 0 });
+        assert_eq!(set.validation_report().len(), 2);
+    }
+
+    #[test]
+    fn validated_methods_return_rejections() {
+        let mut set = SingletonSet::new();
+        set.register_validator(ordered);
+
+        assert_eq!(
+            set.validated_insert(PoolConfig { min: 1, max: 2 }).unwrap(),
+            None
+        );
+        let err = set
+            .validated_insert(PoolConfig { min: 3, max: 2 })
+            .unwrap_err();
+        assert!(err.was_rejected());
+        assert_eq!(
+            err.to_string(),
+            set.validation_report().failures()[0].to_string()
+        );
+
+        assert_eq!(
+            set.validated_with_mut(|c: &mut PoolConfig| c.max += 1).ok(),
+            Some(())
+        );
+        assert!(set
+            .validated_with_mut(|c: &mut PoolConfig| c.min = 9)
+            .is_err());
+        assert_eq!(set.get::<PoolConfig>(), &PoolConfig { min: 1, max: 3 });
+
+        // Failures that are only reported don't make the methods fail.
+        set.set_validation_policy(ValidationPolicy::Report);
+        let replaced = set.validated_insert(PoolConfig { min: 5, max: 0 }).unwrap();
+        assert_eq!(replaced, Some(PoolConfig { min: 1, max: 3 }));
+        assert_eq!(set.validation_report().len(), 3);
+    }
+
+    #[test]
+    fn every_insertion_path_is_validated() {
+        let mut set = SingletonSet::new();
+        set.register_validator(ordered);
+
+        let invalid = || PoolConfig { min: 2, max: 1 };
+        assert_eq!(set.insert_unsized(Box::new(invalid())), None);
+        assert!(set.is_empty());
+
+        let mut set = set.journaled(10);
+        assert!(!set.insert(invalid()));
+        assert_eq!(set.undo_len(), 0);
+        assert!(!set.insert(PoolConfig { min: 1, max: 1 }));
+
+        // Undoing and redoing restores values without validating them
+        // again.
+        assert!(set.undo());
+        assert!(set.redo());
+        assert_eq!(set.get::<PoolConfig>().min, 1);
+        assert_eq!(set.validation_report().len(), 2);
+    }
+
+    #[test]
+    fn changes_to_values_that_cant_be_cloned_are_kept() {
+        #[derive(Debug, Default)]
+        struct Port(u16);
+
+        let mut set = SingletonSet::new();
+        set.register_validator_without_undo(|port: &Port| match port.0 {
+            0 => Err("port 0 is reserved"),
+            _ => Ok(()),
+        });
+
+        set.insert(Port(80));
+        assert!(set.validated_insert(Port(0)).unwrap_err().was_rejected());
+        assert_eq!(set.get::<Port>().0, 80);
+
+        assert!(set.validated_with_mut(|port: &mut Port| port.0 = 0).is_ok());
+        assert_eq!(set.get::<Port>().0, 0);
+        assert!(!set.validation_report().failures()[1].was_rejected());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid value")]
+    fn rejected_pinned_values_panic() {
+        let mut set = SingletonSet::new();
+        set.register_validator(ordered);
+        set.insert_pinned(PoolConfig { min: 1, max: 0 });
+    }
+}
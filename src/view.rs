@@ -0,0 +1,386 @@
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+};
+
+use crate::{SingletonSet, Type, TypeSet, Types, TypesInner};
+
+/// An immutable view of a [`SingletonSet`], which may only expose some of
+/// its types.
+///
+/// A view is created with [`SingletonSet::view()`], which exposes every
+/// type, or with [`SingletonSet::restricted()`] and
+/// [`SingletonSet::restricted_to()`], which expose only the types on an
+/// allowlist. Types that are not on the allowlist appear to be absent from
+/// the view, even if the set holds them, which makes views suitable for
+/// handing parts of a set to code that shouldn't see the rest of it.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::{SingletonSet, Type};
+///
+/// struct Secret;
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(1u8);
+/// set.insert("public");
+/// set.insert(Secret);
+///
+/// let view = set.restricted::<(u8, &str, u16)>();
+/// assert_eq!(view.try_get::<u8>(), Some(&1));
+/// assert!(!view.contains::<Secret>());
+/// assert!(view.try_get::<Secret>().is_none());
+/// assert!(view.types().eq([Type::of::<u8>(), Type::of::<&str>()].iter()));
+/// assert_eq!(view.len(), 2);
+/// ```
+///
+/// [`SingletonSet::view()`]: crate::SingletonSet::view()
+/// [`SingletonSet::restricted()`]: crate::SingletonSet::restricted()
+/// [`SingletonSet::restricted_to()`]: crate::SingletonSet::restricted_to()
+#[derive(Clone)]
+pub struct SingletonSetView<'a> {
+    set: &'a SingletonSet,
+    allowed: Option<TypeSet>,
+}
+
+impl<'a> SingletonSetView<'a> {
+    pub(crate) fn new(set: &'a SingletonSet, allowed: Option<TypeSet>) -> Self {
+        SingletonSetView { set, allowed }
+    }
+
+    /// Returns true if the view exposes the slot of the [`Type`], whether
+    /// or not the set holds a value for it.
+    pub fn allows(&self, t: &Type) -> bool {
+        self.allowed
+            .as_ref()
+            .map_or(true, |allowed| allowed.contains_type(t))
+    }
+
+    /// Returns the number of elements the view exposes.
+    pub fn len(&self) -> usize {
+        match &self.allowed {
+            Some(_) => self.types().count(),
+            None => self.set.len(),
+        }
+    }
+
+    /// Returns true if the view exposes no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the type is exposed by the view and represented in
+    /// the set.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.contains_type(&Type::of::<T>())
+    }
+
+    /// Returns true if the supplied [`Type`] is exposed by the view and
+    /// represented in the set.
+    pub fn contains_type(&self, t: &Type) -> bool {
+        self.allows(t) && self.set.contains_type(t)
+    }
+
+    /// Returns an iterator that visits each [`Type`] exposed by the view,
+    /// in the order of the set.
+    pub fn types(&self) -> Types<'_> {
+        match &self.allowed {
+            Some(allowed) => Types(TypesInner::Allowed(self.set.0.keys(), allowed)),
+            None => self.set.types(),
+        }
+    }
+
+    /// Returns an immutable reference to the value of the specified type,
+    /// if it exists and is exposed by the view.
+    pub fn try_get<T>(&self) -> Option<&'a T>
+    where
+        T: 'static,
+    {
+        if !self.allows(&Type::of::<T>()) {
+            return None;
+        }
+
+        self.set.try_get()
+    }
+
+    /// Returns an immutable reference to the value of the specified type.
+    ///
+    /// # Safety
+    ///
+    /// This method panics if there is no existing value for the given type,
+    /// or if the view doesn't expose it. If this is not acceptable, use
+    /// [`.try_get()`].
+    ///
+    /// [`.try_get()`]: Self::try_get()
+    pub fn get<T>(&self) -> &'a T
+    where
+        T: 'static,
+    {
+        self.try_get()
+            .expect(".try_get() should be used if the type might not be in the view")
+    }
+
+    /// Returns an immutable reference to the value in the slot of the given
+    /// [`Type`], if it exists and is exposed by the view.
+    pub fn get_by_type(&self, t: &Type) -> Option<&'a dyn Any> {
+        if !self.allows(t) {
+            return None;
+        }
+
+        self.set.get_by_type(t)
+    }
+}
+
+// Only the exposed types are listed, so that debugging output doesn't
+// reveal what a view hides.
+impl Debug for SingletonSetView<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingletonSetView")
+            .field("types", &self.types().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// A mutable view of a [`SingletonSet`], which exposes only the types on
+/// an allowlist and can only change their slots.
+///
+/// A mutable view is created with [`SingletonSet::restricted_mut()`] or
+/// [`SingletonSet::restricted_to_mut()`]. Like a [`SingletonSetView`], it
+/// hides every type that is not on its allowlist. Inserting a value of a
+/// hidden type fails with [`NotAllowed`], and hidden slots can't be
+/// borrowed mutably or removed.
+///
+/// # Example
+///
+/// ```
+/// use singletonset::SingletonSet;
+///
+/// let mut set = SingletonSet::new();
+/// set.insert(1u8);
+/// set.insert("read-only");
+///
+/// let mut view = set.restricted_mut::<(u8, u16)>();
+/// *view.try_get_mut::<u8>().unwrap() += 1;
+/// assert!(view.insert(2u16).is_ok());
+/// assert!(view.insert("changed").is_err());
+/// assert_eq!(view.remove::<&str>(), None);
+///
+/// assert_eq!(set.get::<u8>(), &2);
+/// assert_eq!(set.get::<&str>(), &"read-only");
+/// ```
+///
+/// [`SingletonSet::restricted_mut()`]: crate::SingletonSet::restricted_mut()
+/// [`SingletonSet::restricted_to_mut()`]: crate::SingletonSet::restricted_to_mut()
+pub struct SingletonSetViewMut<'a> {
+    set: &'a mut SingletonSet,
+    allowed: TypeSet,
+}
+
+impl<'a> SingletonSetViewMut<'a> {
+    pub(crate) fn new(set: &'a mut SingletonSet, allowed: TypeSet) -> Self {
+        SingletonSetViewMut { set, allowed }
+    }
+
+    /// Returns an immutable view that exposes the same types as this one.
+    pub fn as_view(&self) -> SingletonSetView<'_> {
+        SingletonSetView::new(self.set, Some(self.allowed.clone()))
+    }
+
+    /// Returns true if the view exposes the slot of the [`Type`], whether
+    /// or not the set holds a value for it.
+    pub fn allows(&self, t: &Type) -> bool {
+        self.allowed.contains_type(t)
+    }
+
+    /// Returns the number of elements the view exposes.
+    pub fn len(&self) -> usize {
+        self.types().count()
+    }
+
+    /// Returns true if the view exposes no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the type is exposed by the view and represented in
+    /// the set.
+    pub fn contains<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.contains_type(&Type::of::<T>())
+    }
+
+    /// Returns true if the supplied [`Type`] is exposed by the view and
+    /// represented in the set.
+    pub fn contains_type(&self, t: &Type) -> bool {
+        self.allows(t) && self.set.contains_type(t)
+    }
+
+    /// Returns an iterator that visits each [`Type`] exposed by the view,
+    /// in the order of the set.
+    pub fn types(&self) -> Types<'_> {
+        Types(TypesInner::Allowed(self.set.0.keys(), &self.allowed))
+    }
+
+    /// Returns an immutable reference to the value of the specified type,
+    /// if it exists and is exposed by the view.
+    pub fn try_get<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        if !self.allows(&Type::of::<T>()) {
+            return None;
+        }
+
+        self.set.try_get()
+    }
+
+    /// Returns a mutable reference to the value of the specified type, if
+    /// it exists and is exposed by the view.
+    pub fn try_get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        if !self.allows(&Type::of::<T>()) {
+            return None;
+        }
+
+        self.set.try_get_mut()
+    }
+
+    /// Inserts a value into the inferred type's slot, if the view exposes
+    /// it.
+    ///
+    /// If the slot already held a value, that value is returned. If the
+    /// view doesn't expose the type, the set is left untouched.
+    pub fn insert<T>(&mut self, value: T) -> Result<Option<T>, NotAllowed>
+    where
+        T: 'static,
+    {
+        let t = Type::of::<T>();
+        if !self.allows(&t) {
+            return Err(NotAllowed(t));
+        }
+
+        Ok(self.set.insert(value))
+    }
+
+    /// Removes the value from the inferred type's slot and returns it, if
+    /// it exists and is exposed by the view.
+    pub fn remove<T>(&mut self) -> Option<T>
+    where
+        T: 'static,
+    {
+        if !self.allows(&Type::of::<T>()) {
+            return None;
+        }
+
+        self.set.remove()
+    }
+}
+
+impl Debug for SingletonSetViewMut<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingletonSetViewMut")
+            .field("types", &self.types().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// The error returned when a [`SingletonSetViewMut`] is asked to insert a
+/// value of a type that it doesn't expose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotAllowed(Type);
+
+impl NotAllowed {
+    /// Returns the [`Type`] of the rejected value.
+    pub fn to_type(&self) -> Type {
+        self.0
+    }
+}
+
+impl Display for NotAllowed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "type {} is not allowed in this view", self.0)
+    }
+}
+
+impl Error for NotAllowed {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restricted_views_hide_other_types() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert(2u16);
+        set.insert(3u32);
+
+        let view = set.view();
+        assert_eq!(view.len(), 3);
+        assert!(view.types().eq(set.types()));
+
+        let allowed: TypeSet = [Type::of::<u32>(), Type::of::<u8>()].into_iter().collect();
+        let view = set.restricted_to(&allowed);
+        assert!(view
+            .types()
+            .eq([Type::of::<u8>(), Type::of::<u32>()].iter()));
+        assert!(view.contains::<u32>());
+        assert!(!view.contains_type(&Type::of::<u16>()));
+        assert!(view.get_by_type(&Type::of::<u16>()).is_none());
+        assert_eq!(view.get::<u8>(), &1);
+        assert!(!view.is_empty());
+
+        let view = set.restricted::<(u64,)>();
+        assert!(view.allows(&Type::of::<u64>()));
+        assert!(view.is_empty());
+    }
+
+    #[test]
+    fn debug_output_only_lists_exposed_types() {
+        struct Secret;
+
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert(Secret);
+
+        let view = set.restricted::<(u8,)>();
+        assert!(!format!("{:?}", view).contains("Secret"));
+        assert!(format!("{:?}", view).contains("\"u8\""));
+        assert!(format!("{:?}", set.view()).contains("Secret"));
+
+        let view = set.restricted_mut::<(u8, u16)>();
+        assert!(!format!("{:?}", view).contains("Secret"));
+    }
+
+    #[test]
+    fn mutable_views_only_write_allowed_types() {
+        let mut set = SingletonSet::new();
+        set.insert(1u8);
+        set.insert("hidden");
+
+        let mut view = set.restricted_mut::<(u8, String)>();
+        assert_eq!(view.insert(2u8), Ok(Some(1)));
+        assert_eq!(view.insert("x".to_string()), Ok(None));
+        assert!(view.try_get_mut::<&str>().is_none());
+        assert_eq!(view.remove::<&str>(), None);
+
+        let err = view.insert(3u16).unwrap_err();
+        assert_eq!(err.to_type(), Type::of::<u16>());
+        assert_eq!(err.to_string(), "type u16 is not allowed in this view");
+        assert_eq!(view.as_view().len(), 2);
+        assert_eq!(view.remove::<u8>(), Some(2));
+
+        assert!(set
+            .types()
+            .eq([Type::of::<&str>(), Type::of::<String>()].iter()));
+    }
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "derive")]
+
+use singletonset::{FromSingletonSet, IntoSingletonSet, SingletonSet, Type};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Config(&'static str);
+
+#[derive(Debug, PartialEq)]
+struct Database(u16);
+
+#[derive(Debug, PartialEq, FromSingletonSet, IntoSingletonSet)]
+struct Context {
+    #[singleton(clone)]
+    config: Config,
+    database: Database,
+    #[singleton(optional)]
+    retries: Option<u8>,
+    #[singleton(default)]
+    name: String,
+}
+
+#[test]
+fn from_singleton_set_takes_and_clones_fields() {
+    let mut set = SingletonSet::new();
+    set.insert(Config("prod"));
+    set.insert(Database(5432));
+    set.insert(3u8);
+
+    let context: Context = set.extract().unwrap();
+
+    assert_eq!(
+        context,
+        Context {
+            config: Config("prod"),
+            database: Database(5432),
+            retries: Some(3),
+            name: String::new(),
+        }
+    );
+    assert!(set.contains::<Config>());
+    assert!(!set.contains::<Database>());
+    assert!(!set.contains::<u8>());
+}
+
+#[test]
+fn from_singleton_set_names_missing_fields() {
+    let mut set = SingletonSet::new();
+    set.insert(7u8);
+
+    let err = set.extract::<Context>().unwrap_err();
+
+    assert_eq!(
+        err.iter().collect::<Vec<_>>(),
+        [
+            ("config", &Type::of::<Config>()),
+            ("database", &Type::of::<Database>()),
+        ]
+    );
+    assert!(err.to_string().starts_with("missing fields: `config` ("));
+
+    // Nothing was taken, since the struct could not be built.
+    assert_eq!(set.try_get::<u8>(), Some(&7));
+}
+
+#[test]
+fn into_singleton_set_scatters_fields() {
+    let context = Context {
+        config: Config("dev"),
+        database: Database(1),
+        retries: None,
+        name: "test".to_string(),
+    };
+
+    let set = context.into_singleton_set();
+
+    assert_eq!(set.len(), 3);
+    assert_eq!(set.try_get::<Config>(), Some(&Config("dev")));
+    assert_eq!(set.try_get::<Database>(), Some(&Database(1)));
+    assert_eq!(set.try_get::<String>(), Some(&"test".to_string()));
+    assert!(!set.contains::<u8>());
+}
+
+#[derive(FromSingletonSet, IntoSingletonSet)]
+struct Pair(u8, #[singleton(clone)] u16);
+
+#[test]
+fn tuple_structs_round_trip() {
+    let mut set = SingletonSet::new();
+    set.insert_fields(Pair(1, 2));
+
+    let Pair(a, b) = set.extract().unwrap();
+
+    assert_eq!((a, b), (1, 2));
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn from_singleton_set_leaves_shared_values_alone() {
+    let mut set = SingletonSet::new();
+    set.insert(Config("prod"));
+    set.insert_shared(Database(5432));
+    let arc = set.get_arc::<Database>().unwrap();
+
+    let err = set.extract::<Context>().unwrap_err();
+
+    assert_eq!(err.iter().count(), 0);
+    assert_eq!(
+        err.not_owned().collect::<Vec<_>>(),
+        [("database", &Type::of::<Database>())]
+    );
+    assert!(err
+        .to_string()
+        .starts_with("fields not owned: `database` ("));
+    assert!(set.contains::<Database>());
+
+    drop(arc);
+    assert_eq!(set.extract::<Context>().unwrap().database, Database(5432));
+}
+
+#[test]
+fn from_singleton_set_leaves_pinned_values_alone() {
+    let mut set = SingletonSet::new();
+    set.insert(Config("prod"));
+    set.insert_pinned(Database(5432));
+
+    let err = set.extract::<Context>().unwrap_err();
+
+    assert_eq!(
+        err.not_owned().collect::<Vec<_>>(),
+        [("database", &Type::of::<Database>())]
+    );
+    assert_eq!(set.get::<Database>(), &Database(5432));
+    assert!(set.remove_pinned::<Database>().is_some());
+    assert!(set.extract::<Context>().is_err());
+}
+
+#[allow(dead_code)]
+#[derive(Debug, FromSingletonSet)]
+struct Replicas {
+    primary: Database,
+    replica: Database,
+}
+
+#[test]
+fn from_singleton_set_rejects_fields_taking_the_same_type() {
+    let mut set = SingletonSet::new();
+    set.insert(Database(5432));
+
+    let err = set.extract::<Replicas>().unwrap_err();
+
+    assert_eq!(
+        err.duplicated().collect::<Vec<_>>(),
+        [("replica", &Type::of::<Database>())]
+    );
+    assert!(err
+        .to_string()
+        .starts_with("fields taking a type already taken: `replica` ("));
+    assert_eq!(set.get::<Database>(), &Database(5432));
+}